@@ -0,0 +1,117 @@
+//! Read path benchmarks.
+//!
+//! Compares the current lock-free read path against the previous
+//! design, which stored the result and progress behind mutexes.
+
+// Imports
+use {
+	app_error::AppError,
+	criterion::{Criterion, criterion_group, criterion_main},
+	parking_lot::Mutex,
+	std::{
+		hint,
+		sync::{
+			Arc,
+			atomic::{self, AtomicBool},
+		},
+		thread,
+	},
+	zutil_async_loadable::AsyncLoadable,
+};
+
+/// Previous design
+struct MutexLoadable {
+	/// Result
+	res: Mutex<Option<Result<u64, AppError>>>,
+
+	/// Progress
+	progress: Mutex<Option<u64>>,
+}
+
+impl MutexLoadable {
+	/// Creates a new, loaded, value
+	const fn from_value(value: u64) -> Self {
+		Self {
+			res:      Mutex::new(Some(Ok(value))),
+			progress: Mutex::new(None),
+		}
+	}
+
+	/// Gets the value
+	fn get(&self) -> Option<Result<u64, AppError>> {
+		self.res.lock().clone()
+	}
+
+	/// Gets the progress.
+	///
+	/// If the progress is currently being updated, returns `None`
+	fn progress(&self) -> Option<u64> {
+		self.progress.try_lock().as_deref().copied().flatten()
+	}
+}
+
+fn get(c: &mut Criterion) {
+	let mut group = c.benchmark_group("get");
+
+	let mutex = MutexLoadable::from_value(5);
+	group.bench_function("mutex", |b| b.iter(|| hint::black_box(mutex.get())));
+
+	let loadable = AsyncLoadable::<u64>::from_value(5);
+	group.bench_function("arc_swap", |b| b.iter(|| hint::black_box(loadable.get())));
+
+	group.finish();
+}
+
+fn progress_contended(c: &mut Criterion) {
+	let mut group = c.benchmark_group("progress_contended");
+
+	// Previous design, with a thread constantly updating the progress
+	{
+		let mutex = Arc::new(MutexLoadable::from_value(5));
+		let stop = Arc::new(AtomicBool::new(false));
+		let updater = thread::spawn({
+			let mutex = Arc::clone(&mutex);
+			let stop = Arc::clone(&stop);
+			move || {
+				let mut idx = 0;
+				while !stop.load(atomic::Ordering::Relaxed) {
+					*mutex.progress.lock() = Some(idx);
+					idx += 1;
+				}
+			}
+		});
+
+		// Note: This is mostly fast because it fails to lock and returns `None`.
+		group.bench_function("mutex", |b| b.iter(|| hint::black_box(mutex.progress())));
+
+		stop.store(true, atomic::Ordering::Relaxed);
+		updater.join().expect("Updater panicked");
+	}
+
+	// Current design, with a loader constantly updating the progress
+	{
+		let runtime = tokio::runtime::Builder::new_multi_thread()
+			.worker_threads(1)
+			.build()
+			.expect("Unable to create runtime");
+		let loadable = AsyncLoadable::<u64, u64>::new();
+		let _guard = runtime.enter();
+		let _: Option<_> = loadable.try_load(async |progress| {
+			let mut idx = 0;
+			loop {
+				progress.update(idx);
+				idx += 1;
+				tokio::task::yield_now().await;
+			}
+		});
+
+		group.bench_function("arc_swap", |b| b.iter(|| hint::black_box(loadable.progress())));
+
+		loadable.stop_loading();
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, get, progress_contended);
+criterion_main!(benches);
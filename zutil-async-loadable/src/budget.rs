@@ -0,0 +1,270 @@
+//! Memory budget
+
+// Imports
+use {
+	crate::{AsyncLoadable, CallbackHandle, Inner, LoadableId},
+	parking_lot::Mutex,
+	std::{
+		fmt,
+		sync::{
+			Arc,
+			LazyLock,
+			Weak,
+			atomic::{self, AtomicU64, AtomicUsize},
+		},
+	},
+};
+
+/// Access clock.
+///
+/// Starts at 1, since 0 is used for untracked loadables.
+static CLOCK: AtomicU64 = AtomicU64::new(1);
+
+/// Returns the next access tick
+pub fn tick() -> u64 {
+	CLOCK.fetch_add(1, atomic::Ordering::Relaxed) + 1
+}
+
+/// Budget member.
+///
+/// Type-erased interface over a loadable tracked by a budget.
+trait BudgetMember: Send + Sync {
+	/// Returns the id of the loadable
+	fn id(&self) -> Option<LoadableId>;
+
+	/// Returns the last access of the loadable, as well as
+	/// the weight of it's value, if it's loaded successfully and
+	/// may be evicted.
+	fn entry(&self) -> Option<(u64, usize)>;
+
+	/// Evicts the value of the loadable, if it isn't loading
+	fn evict(&self);
+}
+
+/// Budget member for a loadable
+struct Member<T, P, W: ?Sized> {
+	/// Loadable
+	inner: Weak<Inner<T, P>>,
+
+	/// Loaded callback handle
+	_on_loaded: CallbackHandle,
+
+	/// Weigher
+	weigh: W,
+}
+
+impl<T, P, W> BudgetMember for Member<T, P, W>
+where
+	T: Send + Sync + 'static,
+	P: Send + Sync + 'static,
+	W: Fn(&T) -> usize + Send + Sync,
+{
+	fn id(&self) -> Option<LoadableId> {
+		self.inner.upgrade().map(|inner| inner.id)
+	}
+
+	fn entry(&self) -> Option<(u64, usize)> {
+		let inner = self.inner.upgrade()?;
+		let last_access = inner.last_access.load(atomic::Ordering::Relaxed);
+		let res = inner.res.load();
+		let value = res.as_deref()?.as_ref().ok()?;
+
+		Some((last_access, (self.weigh)(value)))
+	}
+
+	fn evict(&self) {
+		let Some(inner) = self.inner.upgrade() else {
+			return;
+		};
+
+		// Note: We don't invalidate any dependents, since the value
+		//       would be the same once reloaded.
+		let task = inner.load.task.lock();
+		if task.is_running() {
+			return;
+		}
+		inner.res.store(None);
+		inner.fallback.clear();
+		drop(task);
+
+		inner.load.changed();
+	}
+}
+
+/// Memory budget inner
+struct BudgetInner {
+	/// Limit
+	limit: AtomicUsize,
+
+	/// Members
+	members: Mutex<Vec<Arc<dyn BudgetMember>>>,
+}
+
+impl BudgetInner {
+	/// Returns all alive members
+	fn members(&self) -> Vec<Arc<dyn BudgetMember>> {
+		let mut members = self.members.lock();
+		members.retain(|member| member.id().is_some());
+		members.clone()
+	}
+
+	/// Evicts the least recently accessed values until we're within the limit.
+	///
+	/// The loadable with id `keep` is never evicted.
+	fn enforce(&self, keep: Option<LoadableId>) {
+		let limit = self.limit.load(atomic::Ordering::Relaxed);
+
+		// Note: We weigh the members without the lock, since the weigher
+		//       might access this budget.
+		let members = self.members();
+		let mut entries = members
+			.iter()
+			.filter_map(|member| {
+				member
+					.entry()
+					.map(|(last_access, weight)| (last_access, weight, member))
+			})
+			.collect::<Vec<_>>();
+
+		let mut used = entries.iter().map(|&(_, weight, _)| weight).sum::<usize>();
+		entries.sort_by_key(|&(last_access, ..)| last_access);
+		let evict = entries
+			.into_iter()
+			.filter(|(.., member)| member.id() != keep)
+			.take_while(|&(_, weight, _)| match used > limit {
+				true => {
+					used -= weight;
+					true
+				},
+				false => false,
+			})
+			.map(|(.., member)| Arc::clone(member))
+			.collect::<Vec<_>>();
+
+		for member in evict {
+			member.evict();
+		}
+	}
+}
+
+/// A memory budget.
+///
+/// Loadables registered in a budget report the weight of their values.
+/// Whenever a registered loadable finishes loading and the total weight
+/// exceeds the limit, the least recently accessed values are evicted.
+///
+/// Evicted loadables behave as if they were reset, but without resetting
+/// their dependents. An [`AsyncLazy`](crate::AsyncLazy) will transparently
+/// reload it's value on the next [`get_or_load`](crate::AsyncLazy::get_or_load).
+pub struct MemoryBudget {
+	/// Inner
+	inner: Arc<BudgetInner>,
+}
+
+impl MemoryBudget {
+	/// Creates a new budget with a limit
+	#[must_use]
+	pub fn new(limit: usize) -> Self {
+		Self {
+			inner: Arc::new(BudgetInner {
+				limit:   AtomicUsize::new(limit),
+				members: Mutex::new(vec![]),
+			}),
+		}
+	}
+
+	/// Returns the global budget.
+	///
+	/// Starts without a limit.
+	#[must_use]
+	pub fn global() -> &'static Self {
+		/// Global budget
+		static GLOBAL: LazyLock<MemoryBudget> = LazyLock::new(|| MemoryBudget::new(usize::MAX));
+
+		&GLOBAL
+	}
+
+	/// Returns the limit of this budget
+	#[must_use]
+	pub fn limit(&self) -> usize {
+		self.inner.limit.load(atomic::Ordering::Relaxed)
+	}
+
+	/// Sets the limit of this budget.
+	///
+	/// Evicts values if the new limit is exceeded.
+	pub fn set_limit(&self, limit: usize) {
+		self.inner.limit.store(limit, atomic::Ordering::Relaxed);
+		self.inner.enforce(None);
+	}
+
+	/// Returns the total weight of all loaded values in this budget
+	#[must_use]
+	pub fn used(&self) -> usize {
+		self.inner
+			.members()
+			.iter()
+			.filter_map(|member| member.entry())
+			.map(|(_, weight)| weight)
+			.sum()
+	}
+
+	/// Adds a loadable to this budget.
+	///
+	/// `weigh` returns the weight of the loadable's value.
+	/// Errors aren't weighed, and are never evicted.
+	///
+	/// The budget only keeps the loadable registered while it's alive.
+	///
+	/// If the loadable is already in the budget, does nothing.
+	pub fn add<T, P, W>(&self, loadable: &AsyncLoadable<T, P>, weigh: W)
+	where
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+		W: Fn(&T) -> usize + Send + Sync + 'static,
+	{
+		let id = loadable.id();
+		let inner = &loadable.inner;
+		let mut members = self.inner.members.lock();
+		if members.iter().any(|member| member.id() == Some(id)) {
+			return;
+		}
+		inner.last_access.store(self::tick(), atomic::Ordering::Relaxed);
+
+		let budget = Arc::downgrade(&self.inner);
+		let loadable_inner = Arc::downgrade(inner);
+		let on_loaded = loadable.on_loaded(move |_| {
+			if let Some(inner) = loadable_inner.upgrade() {
+				inner.last_access.store(self::tick(), atomic::Ordering::Relaxed);
+			}
+			if let Some(budget) = budget.upgrade() {
+				budget.enforce(Some(id));
+			}
+		});
+		members.push(Arc::new(Member {
+			inner: Arc::downgrade(inner),
+			_on_loaded: on_loaded,
+			weigh,
+		}));
+		drop(members);
+
+		self.inner.enforce(None);
+	}
+
+	/// Removes a loadable from this budget.
+	///
+	/// If the loadable isn't in the budget, does nothing.
+	pub fn remove<T, P>(&self, loadable: &AsyncLoadable<T, P>) {
+		let id = loadable.id();
+		self.inner.members.lock().retain(|member| member.id() != Some(id));
+	}
+}
+
+impl fmt::Debug for MemoryBudget {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("MemoryBudget")
+			.field("limit", &self.limit())
+			.field("used", &self.used())
+			.finish_non_exhaustive()
+	}
+}
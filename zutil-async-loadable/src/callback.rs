@@ -0,0 +1,158 @@
+//! Callbacks
+
+// Imports
+use {
+	crate::{AsyncLoadable, Inner},
+	app_error::AppError,
+	parking_lot::Mutex,
+	std::{
+		fmt,
+		sync::{
+			Arc,
+			atomic::{self, AtomicU64},
+		},
+	},
+};
+
+/// Callbacks
+pub struct Callbacks<F: ?Sized> {
+	/// Callbacks, with their ids
+	callbacks: Mutex<Vec<(u64, Arc<F>)>>,
+
+	/// Next id
+	next_id: AtomicU64,
+}
+
+impl<F: ?Sized> Callbacks<F> {
+	/// Creates an empty set of callbacks
+	pub const fn new() -> Self {
+		Self {
+			callbacks: Mutex::new(vec![]),
+			next_id:   AtomicU64::new(0),
+		}
+	}
+
+	/// Adds a callback, returning it's id
+	pub fn add(&self, f: Arc<F>) -> u64 {
+		let id = self.next_id.fetch_add(1, atomic::Ordering::Relaxed);
+		self.callbacks.lock().push((id, f));
+		id
+	}
+
+	/// Removes a callback by id
+	pub fn remove(&self, id: u64) {
+		self.callbacks.lock().retain(|&(cur_id, _)| cur_id != id);
+	}
+
+	/// Calls all callbacks.
+	///
+	/// Callbacks are called without any locks held, so they may add or
+	/// remove callbacks.
+	pub fn call_all(&self, mut call: impl FnMut(&F)) {
+		let callbacks = self
+			.callbacks
+			.lock()
+			.iter()
+			.map(|(_, f)| Arc::clone(f))
+			.collect::<Vec<_>>();
+		for f in callbacks {
+			call(&f);
+		}
+	}
+}
+
+/// Change callback
+pub type OnChange = dyn Fn() + Send + Sync;
+
+/// Loaded callback
+pub type OnLoaded<T> = dyn Fn(&Result<T, AppError>) + Send + Sync;
+
+/// Callback handle.
+///
+/// Unregisters the callback once dropped, unless detached
+/// with [`CallbackHandle::detach`].
+#[must_use = "Dropping the handle unregisters the callback"]
+pub struct CallbackHandle {
+	/// Unregisters the callback
+	unregister: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl CallbackHandle {
+	/// Unregisters the callback.
+	///
+	/// This is the same as dropping the handle.
+	pub fn unregister(self) {}
+
+	/// Detaches this handle, keeping the callback registered
+	/// for as long as the loadable is alive.
+	pub fn detach(mut self) {
+		self.unregister = None;
+	}
+}
+
+impl Drop for CallbackHandle {
+	fn drop(&mut self) {
+		if let Some(unregister) = self.unregister.take() {
+			unregister();
+		}
+	}
+}
+
+impl fmt::Debug for CallbackHandle {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CallbackHandle")
+			.field("detached", &self.unregister.is_none())
+			.finish()
+	}
+}
+
+impl<T, P> AsyncLoadable<T, P> {
+	/// Registers a callback for whenever this loadable changes.
+	///
+	/// This includes finishing loading, progress updates, resets and
+	/// stopping the loading.
+	///
+	/// The callback is called from whichever thread caused the change,
+	/// which might be the loader task.
+	pub fn on_change<F>(&self, f: F) -> CallbackHandle
+	where
+		F: Fn() + Send + Sync + 'static,
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let id = self.inner.load.on_change.add(Arc::new(f));
+		self::handle(&self.inner, move |inner| inner.load.on_change.remove(id))
+	}
+
+	/// Registers a callback for whenever this loadable finishes loading.
+	///
+	/// The callback is called from the loader task, after the result is written.
+	pub fn on_loaded<F>(&self, f: F) -> CallbackHandle
+	where
+		F: Fn(&Result<T, AppError>) + Send + Sync + 'static,
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let id = self.inner.on_loaded.add(Arc::new(f));
+		self::handle(&self.inner, move |inner| inner.on_loaded.remove(id))
+	}
+}
+
+/// Creates a callback handle that calls `unregister` if `inner` is still alive.
+fn handle<T, P>(
+	inner: &Arc<Inner<T, P>>,
+	unregister: impl FnOnce(&Inner<T, P>) + Send + Sync + 'static,
+) -> CallbackHandle
+where
+	T: Send + Sync + 'static,
+	P: Send + Sync + 'static,
+{
+	let inner = Arc::downgrade(inner);
+	CallbackHandle {
+		unregister: Some(Box::new(move || {
+			if let Some(inner) = inner.upgrade() {
+				unregister(&inner);
+			}
+		})),
+	}
+}
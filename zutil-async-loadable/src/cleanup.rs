@@ -0,0 +1,97 @@
+//! Cleanup actions
+
+// Imports
+use {
+	parking_lot::Mutex,
+	std::{panic, pin::Pin, sync::Arc, time::Duration},
+	tokio::runtime,
+};
+
+/// Timeout for running all cleanup actions of a load
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Cleanup action
+type Action = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Cleanup actions of a load
+#[derive(Default)]
+pub struct Cleanups {
+	/// Actions
+	actions: Mutex<Vec<Action>>,
+}
+
+impl Cleanups {
+	/// Adds a cleanup action
+	pub fn add<F>(&self, f: F)
+	where
+		F: Future<Output = ()> + Send + 'static,
+	{
+		self.actions.lock().push(Box::pin(f));
+	}
+
+	/// Removes all cleanup actions without running them
+	pub fn clear(&self) {
+		self.actions.lock().clear();
+	}
+
+	/// Runs all cleanup actions in a new task, in reverse order of registration.
+	///
+	/// If the runtime has timers enabled, the actions are cancelled after [`TIMEOUT`].
+	pub fn run(&self, runtime: &runtime::Handle) {
+		let actions = std::mem::take(&mut *self.actions.lock());
+		if actions.is_empty() {
+			return;
+		}
+
+		// Note: We don't care whether the actions finished, this is best effort,
+		//       so we detach the task.
+		let task = runtime.spawn(async move {
+			let actions = async move {
+				for action in actions.into_iter().rev() {
+					action.await;
+				}
+			};
+
+			// Note: Creating a timer panics if the runtime doesn't have them enabled,
+			//       and there's no other way to check, so we do it within the task,
+			//       instead of when dropping the load.
+			match panic::catch_unwind(|| tokio::time::sleep(Duration::ZERO)).is_ok() {
+				true => _ = tokio::time::timeout(self::TIMEOUT, actions).await,
+				false => actions.await,
+			}
+		});
+		drop(task);
+	}
+}
+
+/// Runs cleanup actions on drop.
+///
+/// Moved into the loader task, so that the actions also run when the task is aborted.
+pub struct CleanupGuard {
+	/// Cleanups
+	cleanups: Arc<Cleanups>,
+
+	/// Runtime to run them on
+	runtime: runtime::Handle,
+}
+
+impl CleanupGuard {
+	/// Creates a new guard on the current runtime
+	pub fn new(cleanups: Arc<Cleanups>) -> Self {
+		Self {
+			cleanups,
+			runtime: runtime::Handle::current(),
+		}
+	}
+
+	/// Disarms this guard, so no actions are run
+	pub fn disarm(&self) {
+		self.cleanups.clear();
+	}
+}
+
+impl Drop for CleanupGuard {
+	fn drop(&mut self) {
+		self.cleanups.run(&self.runtime);
+	}
+}
@@ -0,0 +1,396 @@
+//! Dependency tracking
+
+// Imports
+use {
+	crate::{AsyncLoadable, Inner, ProgressUpdater},
+	app_error::{AppError, app_error},
+	parking_lot::Mutex,
+	std::{
+		any,
+		collections::HashSet,
+		fmt,
+		sync::{
+			Arc,
+			Weak,
+			atomic::{self, AtomicU64},
+		},
+	},
+};
+
+/// Loadable id.
+///
+/// Uniquely identifies a loadable (and all of it's reference-counted clones)
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LoadableId(u64);
+
+impl LoadableId {
+	/// Creates a new, unique, id
+	pub(crate) fn new() -> Self {
+		/// Next id
+		static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+		Self(NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed))
+	}
+}
+
+impl fmt::Display for LoadableId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "#{}", self.0)
+	}
+}
+
+/// Dependency graph node.
+///
+/// Type-erased view over a loadable used for walking the dependency graph.
+pub trait DependencyNode: Send + Sync {
+	/// Returns the id of this node
+	fn id(&self) -> LoadableId;
+
+	/// Returns the type name of the value of this node
+	fn type_name(&self) -> &'static str;
+
+	/// Returns the dependencies of this node
+	fn deps(&self) -> &Mutex<Deps>;
+
+	/// Returns if this node is loaded
+	fn is_loaded(&self) -> bool;
+
+	/// Returns if this node is loading
+	fn is_loading(&self) -> bool;
+
+	/// Invalidates this node, stopping any loading and resetting the value.
+	///
+	/// Does not cascade to the dependents.
+	fn invalidate(&self);
+}
+
+impl<T, P> DependencyNode for Inner<T, P>
+where
+	T: Send + Sync + 'static,
+	P: Send + Sync + 'static,
+{
+	fn id(&self) -> LoadableId {
+		self.id
+	}
+
+	fn type_name(&self) -> &'static str {
+		any::type_name::<T>()
+	}
+
+	fn deps(&self) -> &Mutex<Deps> {
+		&self.deps
+	}
+
+	fn is_loaded(&self) -> bool {
+		self.res.load().is_some()
+	}
+
+	fn is_loading(&self) -> bool {
+		self.load.task.lock().is_running()
+	}
+
+	fn invalidate(&self) {
+		// Note: Stopping the task allows a reload to start a new task immediately,
+		//       instead of waiting for the aborted one to finish.
+		let mut task = self.load.task.lock();
+		task.stop();
+		self.res.store(None);
+		self.fallback.clear();
+		self.load.progress.store(None);
+		drop(task);
+
+		self.load.changed();
+	}
+}
+
+/// Graph lock.
+///
+/// Locked while adding dependencies, so that cycle checks and insertions
+/// are atomic across the whole graph.
+static GRAPH_LOCK: Mutex<()> = Mutex::new(());
+
+/// Reloader
+type Reloader = Arc<dyn Fn() + Send + Sync>;
+
+/// Dependencies of a loadable
+#[derive(Default)]
+pub struct Deps {
+	/// Loadables we depend on
+	dependencies: Vec<Weak<dyn DependencyNode>>,
+
+	/// Loadables that depend on us
+	dependents: Vec<Weak<dyn DependencyNode>>,
+
+	/// Reloader, called after being invalidated
+	reloader: Option<Reloader>,
+}
+
+impl Deps {
+	/// Returns all live dependencies
+	fn dependencies(&mut self) -> Vec<Arc<dyn DependencyNode>> {
+		self::upgrade_all(&mut self.dependencies)
+	}
+
+	/// Returns all live dependents
+	fn dependents(&mut self) -> Vec<Arc<dyn DependencyNode>> {
+		self::upgrade_all(&mut self.dependents)
+	}
+}
+
+/// Upgrades all nodes in `nodes`, removing any dead ones.
+fn upgrade_all(nodes: &mut Vec<Weak<dyn DependencyNode>>) -> Vec<Arc<dyn DependencyNode>> {
+	let mut live = Vec::with_capacity(nodes.len());
+	nodes.retain(|node| match node.upgrade() {
+		Some(node) => {
+			live.push(node);
+			true
+		},
+		None => false,
+	});
+
+	live
+}
+
+/// Invalidates all (transitive) dependents of a node.
+///
+/// Dependents with a reloader are reloaded instead of having their
+/// own dependents invalidated, since those will be invalidated once
+/// the reload finishes.
+pub fn invalidate_dependents(id: LoadableId, deps: &Mutex<Deps>) {
+	let mut visited = HashSet::from([id]);
+	let mut stack = deps.lock().dependents();
+	while let Some(dependent) = stack.pop() {
+		if !visited.insert(dependent.id()) {
+			continue;
+		}
+
+		dependent.invalidate();
+
+		// Note: We don't call the reloader with the lock, since it will
+		//       likely need to access the dependencies.
+		let mut dependent_deps = dependent.deps().lock();
+		match dependent_deps.reloader.clone() {
+			Some(reloader) => {
+				drop(dependent_deps);
+				reloader();
+			},
+			None => stack.extend(dependent_deps.dependents()),
+		}
+	}
+}
+
+/// Dependency graph
+#[derive(Clone, Debug)]
+pub struct DependencyGraph {
+	/// Nodes
+	pub nodes: Vec<DependencyGraphNode>,
+
+	/// Edges, as `(dependency, dependent)`
+	pub edges: Vec<(LoadableId, LoadableId)>,
+}
+
+/// Dependency graph node
+#[derive(Clone, Debug)]
+pub struct DependencyGraphNode {
+	/// Id
+	pub id: LoadableId,
+
+	/// Type name of the value
+	pub type_name: &'static str,
+
+	/// Whether the node is loaded
+	pub is_loaded: bool,
+
+	/// Whether the node is loading
+	pub is_loading: bool,
+}
+
+impl<T, P> AsyncLoadable<T, P> {
+	/// Returns the id of this loadable
+	#[must_use]
+	pub fn id(&self) -> LoadableId {
+		self.inner.id
+	}
+
+	/// Adds a dependency to this loadable.
+	///
+	/// Whenever `dependency` is reset or reloaded, this loadable will
+	/// be reset too (as well as any loadables that depend on it).
+	///
+	/// Returns an error if this would create a cycle.
+	pub fn add_dependency<U, Q>(&self, dependency: &AsyncLoadable<U, Q>) -> Result<(), AppError>
+	where
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+		U: Send + Sync + 'static,
+		Q: Send + Sync + 'static,
+	{
+		// Note: We keep the graph locked while checking for cycles, so that a
+		//       concurrent addition can't create one after we've checked.
+		let _graph = GRAPH_LOCK.lock();
+
+		// Check if `dependency` already (transitively) depends on us
+		if dependency.id() == self.id() || self::is_reachable(Arc::clone(&self.inner) as _, dependency.id()) {
+			return Err(app_error!(
+				"Adding dependency {} to {} would create a cycle",
+				dependency.id(),
+				self.id()
+			));
+		}
+
+		let mut deps = self.inner.deps.lock();
+		if deps.dependencies().iter().any(|node| node.id() == dependency.id()) {
+			return Ok(());
+		}
+		deps.dependencies
+			.push(Arc::downgrade(&dependency.inner) as Weak<dyn DependencyNode>);
+		drop(deps);
+
+		dependency
+			.inner
+			.deps
+			.lock()
+			.dependents
+			.push(Arc::downgrade(&self.inner) as Weak<dyn DependencyNode>);
+
+		Ok(())
+	}
+
+	/// Removes a dependency from this loadable.
+	///
+	/// Returns if `dependency` was a dependency.
+	#[expect(clippy::must_use_candidate, reason = "It's fine to ignore the return value")]
+	pub fn remove_dependency<U, Q>(&self, dependency: &AsyncLoadable<U, Q>) -> bool {
+		let mut removed = false;
+		let mut deps = self.inner.deps.lock();
+		deps.dependencies.retain(|node| match node.upgrade() {
+			Some(node) if node.id() == dependency.id() => {
+				removed = true;
+				false
+			},
+			Some(_) => true,
+			None => false,
+		});
+		drop(deps);
+
+		dependency
+			.inner
+			.deps
+			.lock()
+			.dependents
+			.retain(|node| node.upgrade().is_some_and(|node| node.id() != self.id()));
+
+		removed
+	}
+
+	/// Returns the ids of all the direct dependencies of this loadable
+	#[must_use]
+	pub fn dependencies(&self) -> Vec<LoadableId> {
+		self.inner
+			.deps
+			.lock()
+			.dependencies()
+			.iter()
+			.map(|node| node.id())
+			.collect()
+	}
+
+	/// Returns the ids of all the direct dependents of this loadable
+	#[must_use]
+	pub fn dependents(&self) -> Vec<LoadableId> {
+		self.inner
+			.deps
+			.lock()
+			.dependents()
+			.iter()
+			.map(|node| node.id())
+			.collect()
+	}
+
+	/// Sets a loader to reload this loadable with, whenever it's
+	/// invalidated by one of it's dependencies.
+	pub fn set_reload_on_invalidate<F, Fut>(&self, f: F)
+	where
+		F: Fn(ProgressUpdater<P>) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<T, AppError>> + Send + 'static,
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let inner = Arc::downgrade(&self.inner);
+		// Note: We reload instead of loading, so that our own dependents are
+		//       invalidated once the reload finishes.
+		#[expect(clippy::redundant_closure, reason = "`&F` doesn't implement `AsyncFnOnce`")]
+		let reloader = move || {
+			if let Some(inner) = inner.upgrade() {
+				let _: Option<_> = Self { inner }.try_reload_discard(|progress| f(progress));
+			}
+		};
+
+		self.inner.deps.lock().reloader = Some(Arc::new(reloader));
+	}
+
+	/// Removes the loader set by [`Self::set_reload_on_invalidate`]
+	pub fn clear_reload_on_invalidate(&self) {
+		self.inner.deps.lock().reloader = None;
+	}
+
+	/// Returns the dependency graph this loadable is a part of.
+	///
+	/// This includes all loadables that are (transitively) connected
+	/// to this one, either as a dependency or as a dependent.
+	#[must_use]
+	pub fn dependency_graph(&self) -> DependencyGraph
+	where
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let mut nodes = vec![];
+		let mut edges = vec![];
+
+		let mut visited = HashSet::new();
+		let mut stack = vec![Arc::clone(&self.inner) as Arc<dyn DependencyNode>];
+		while let Some(node) = stack.pop() {
+			if !visited.insert(node.id()) {
+				continue;
+			}
+
+			nodes.push(DependencyGraphNode {
+				id:         node.id(),
+				type_name:  node.type_name(),
+				is_loaded:  node.is_loaded(),
+				is_loading: node.is_loading(),
+			});
+
+			let mut deps = node.deps().lock();
+			let dependencies = deps.dependencies();
+			let dependents = deps.dependents();
+			drop(deps);
+
+			// Note: We only add the edges to our dependents to avoid duplicates.
+			edges.extend(dependents.iter().map(|dependent| (node.id(), dependent.id())));
+			stack.extend(dependencies);
+			stack.extend(dependents);
+		}
+
+		DependencyGraph { nodes, edges }
+	}
+}
+
+/// Returns if `target` is a (transitive) dependent of `node`.
+fn is_reachable(node: Arc<dyn DependencyNode>, target: LoadableId) -> bool {
+	let mut visited = HashSet::new();
+	let mut stack = vec![node];
+	while let Some(node) = stack.pop() {
+		if node.id() == target {
+			return true;
+		}
+		if !visited.insert(node.id()) {
+			continue;
+		}
+
+		stack.extend(node.deps().lock().dependents());
+	}
+
+	false
+}
@@ -0,0 +1,171 @@
+//! Fallback values
+
+// Imports
+use {
+	crate::AsyncLoadable,
+	app_error::AppError,
+	arc_swap::{ArcSwap, ArcSwapOption},
+	std::sync::Arc,
+};
+
+/// Fallback behavior, for when a loadable fails to load
+#[derive(Clone, Default, Debug)]
+pub enum Fallback<T> {
+	/// No fallback
+	#[default]
+	None,
+
+	/// Fall back to a value
+	Value(T),
+
+	/// Fall back to the last successfully loaded value.
+	///
+	/// The last value is kept until the loadable is reset.
+	LastGood,
+}
+
+/// Fallback state
+pub struct FallbackState<T> {
+	/// Fallback
+	fallback: ArcSwap<Fallback<T>>,
+
+	/// Last successful result
+	last_good: ArcSwapOption<Result<T, AppError>>,
+}
+
+impl<T> FallbackState<T> {
+	/// Creates a new fallback state, without any fallback
+	pub fn new() -> Self {
+		Self {
+			fallback:  ArcSwap::from_pointee(Fallback::None),
+			last_good: ArcSwapOption::empty(),
+		}
+	}
+
+	/// Records a new result.
+	///
+	/// Successful results are only kept if falling back to the last good value
+	pub fn loaded(&self, res: &Arc<Result<T, AppError>>) {
+		if res.is_ok() && matches!(**self.fallback.load(), Fallback::LastGood) {
+			self.last_good.store(Some(Arc::clone(res)));
+		}
+	}
+
+	/// Clears the last successful result
+	pub fn clear(&self) {
+		self.last_good.store(None);
+	}
+}
+
+/// A loaded result, along with a fallback value
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct WithFallback<T> {
+	/// Result
+	pub res: Result<T, AppError>,
+
+	/// Fallback value.
+	///
+	/// Only present if `res` is an error.
+	pub fallback: Option<T>,
+}
+
+impl<T> WithFallback<T> {
+	/// Returns the value, or the fallback value if this is an error.
+	pub fn into_res(self) -> Result<T, AppError> {
+		match (self.res, self.fallback) {
+			(Ok(value), _) | (Err(_), Some(value)) => Ok(value),
+			(Err(err), None) => Err(err),
+		}
+	}
+}
+
+impl<T, P> AsyncLoadable<T, P> {
+	/// Sets the fallback behavior for when this loadable fails to load.
+	///
+	/// Setting [`Fallback::LastGood`] keeps the current value, if loaded
+	/// successfully. Any other fallback forgets the last good value.
+	pub fn set_fallback(&self, fallback: Fallback<T>) {
+		let is_last_good = matches!(fallback, Fallback::LastGood);
+		self.inner.fallback.fallback.store(Arc::new(fallback));
+
+		match is_last_good {
+			true =>
+				if let Some(res) = self.inner.res.load_full() {
+					self.inner.fallback.loaded(&res);
+				},
+			false => self.inner.fallback.clear(),
+		}
+	}
+
+	/// Returns the current fallback value, if any.
+	///
+	/// This is returned regardless of whether the loadable failed.
+	#[must_use]
+	pub fn fallback(&self) -> Option<T>
+	where
+		T: Clone,
+	{
+		match &**self.inner.fallback.fallback.load() {
+			Fallback::None => None,
+			Fallback::Value(value) => Some(value.clone()),
+			Fallback::LastGood => self
+				.inner
+				.fallback
+				.last_good
+				.load()
+				.as_deref()
+				.and_then(|res| res.as_ref().ok())
+				.cloned(),
+		}
+	}
+
+	/// Gets the value of the loadable, along with the fallback value, if it failed.
+	///
+	/// This never blocks.
+	#[must_use]
+	pub fn get_with_fallback(&self) -> Option<WithFallback<T>>
+	where
+		T: Clone,
+	{
+		let res = self.get()?;
+		let fallback = match res {
+			Ok(_) => None,
+			Err(_) => self.fallback(),
+		};
+
+		Some(WithFallback { res, fallback })
+	}
+
+	/// Gets the value of the loadable, or the fallback value, if it failed.
+	///
+	/// This never blocks.
+	#[must_use]
+	pub fn get_or_fallback(&self) -> Option<Result<T, AppError>>
+	where
+		T: Clone,
+	{
+		self.get_with_fallback().map(WithFallback::into_res)
+	}
+
+	/// Waits for this loadable to load, returning the fallback value, if it failed.
+	///
+	/// Like [`AsyncLoadable::wait`], this waits for a load to finish, and doesn't
+	/// return a fallback while loading.
+	///
+	/// # Deadlocks
+	/// See [`AsyncLoadable::wait`].
+	pub async fn wait_or_fallback(&self) -> Result<T, AppError>
+	where
+		T: Clone,
+	{
+		#![expect(
+			clippy::future_not_send,
+			reason = "The future is `Send` when `T` and `P` are `Send + Sync`"
+		)]
+
+		match self.wait().await {
+			Ok(value) => Ok(value),
+			Err(err) => self.fallback().ok_or(err),
+		}
+	}
+}
@@ -0,0 +1,282 @@
+//! Load groups
+
+// Imports
+use {
+	crate::{AsyncLoadable, CallbackHandle, Inner, LoadHandle, LoadableId, ProgressUpdater},
+	app_error::{AppError, app_error},
+	parking_lot::Mutex,
+	std::{
+		fmt,
+		sync::{Arc, Weak},
+	},
+	tokio::sync::Notify,
+};
+
+/// Group member.
+///
+/// Type-erased interface over a loadable's inner state.
+trait GroupMember: Send + Sync {
+	/// Returns the id of this member
+	fn id(&self) -> LoadableId;
+
+	/// Returns if this member is loading
+	fn is_loading(&self) -> bool;
+
+	/// Returns the result of this member, without the value, if loaded
+	fn result(&self) -> Option<Result<(), AppError>>;
+
+	/// Stops loading this member
+	fn stop_loading(&self);
+}
+
+impl<T, P> GroupMember for Inner<T, P>
+where
+	T: Send + Sync + 'static,
+	P: Send + Sync + 'static,
+{
+	fn id(&self) -> LoadableId {
+		self.id
+	}
+
+	fn is_loading(&self) -> bool {
+		self.load.task.lock().is_running()
+	}
+
+	fn result(&self) -> Option<Result<(), AppError>> {
+		self.res.load().as_deref().map(|res| match res {
+			Ok(_) => Ok(()),
+			Err(err) => Err(err.clone()),
+		})
+	}
+
+	fn stop_loading(&self) {
+		Self::stop_loading(self);
+	}
+}
+
+/// Group entry
+struct Entry {
+	/// Member
+	member: Weak<dyn GroupMember>,
+
+	/// Change callback handle
+	_on_change: CallbackHandle,
+}
+
+/// A group of loadables.
+///
+/// Allows cancelling and waiting on all loads registered in it at once.
+///
+/// Once dropped, all loads of the group are cancelled.
+pub struct LoadGroup {
+	/// Entries
+	entries: Mutex<Vec<Entry>>,
+
+	/// Notifies whenever any member changes
+	changed: Arc<Notify>,
+}
+
+impl LoadGroup {
+	/// Creates a new, empty, group
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			entries: Mutex::new(vec![]),
+			changed: Arc::new(Notify::new()),
+		}
+	}
+
+	/// Adds a loadable to this group.
+	///
+	/// The group only keeps a weak reference to the loadable.
+	///
+	/// If the loadable is already in the group, does nothing.
+	pub fn add<T, P>(&self, loadable: &AsyncLoadable<T, P>)
+	where
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let mut entries = self.entries.lock();
+		entries.retain(|entry| entry.member.strong_count() != 0);
+		if entries
+			.iter()
+			.filter_map(|entry| entry.member.upgrade())
+			.any(|member| member.id() == loadable.id())
+		{
+			return;
+		}
+
+		let changed = Arc::clone(&self.changed);
+		let on_change = loadable.on_change(move || changed.notify_waiters());
+		let member = Arc::downgrade(&loadable.inner);
+		entries.push(Entry {
+			member,
+			_on_change: on_change,
+		});
+		drop(entries);
+
+		self.changed.notify_waiters();
+	}
+
+	/// Adds a loadable to this group and tries to load it.
+	///
+	/// See [`AsyncLoadable::try_load`] for details.
+	pub fn try_load<T, P, F>(&self, loadable: &AsyncLoadable<T, P>, f: F) -> Option<LoadHandle<T>>
+	where
+		F: AsyncFnOnce(ProgressUpdater<P>) -> Result<T, AppError>,
+		F::CallOnceFuture: Send + 'static,
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		self.add(loadable);
+		loadable.try_load(f)
+	}
+
+	/// Returns the number of (alive) loadables in this group
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.members().len()
+	}
+
+	/// Returns if this group has no (alive) loadables
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.members().is_empty()
+	}
+
+	/// Stops loading all loadables in this group
+	pub fn cancel(&self) {
+		for member in self.members() {
+			member.stop_loading();
+		}
+	}
+
+	/// Returns the aggregate progress of this group
+	#[must_use]
+	pub fn progress(&self) -> GroupProgress {
+		let mut progress = GroupProgress::default();
+		for member in self.members() {
+			progress.total += 1;
+			match member.result() {
+				_ if member.is_loading() => progress.loading += 1,
+				Some(Ok(())) => progress.loaded += 1,
+				Some(Err(_)) => progress.failed += 1,
+				None => (),
+			}
+		}
+
+		progress
+	}
+
+	/// Waits until no loadables in this group are loading.
+	///
+	/// Returns an error with all failures if any loadable failed, or
+	/// isn't loaded (for example, if it was cancelled).
+	pub async fn wait(&self) -> Result<(), AppError> {
+		self.wait_with_progress(|_| ()).await
+	}
+
+	/// Waits until no loadables in this group are loading, reporting progress.
+	///
+	/// `on_progress` is called with the initial progress, and then each time
+	/// any of the loadables changes.
+	///
+	/// See [`Self::wait`] for details.
+	pub async fn wait_with_progress<F>(&self, mut on_progress: F) -> Result<(), AppError>
+	where
+		F: FnMut(GroupProgress),
+	{
+		loop {
+			// Note: See `AsyncLoadable::wait` for why we get the future before checking.
+			let wait_fut = self.changed.notified();
+
+			let progress = self.progress();
+			on_progress(progress);
+			if progress.loading == 0 {
+				break;
+			}
+
+			wait_fut.await;
+		}
+
+		let errs = self
+			.members()
+			.into_iter()
+			.filter_map(|member| match member.result() {
+				Some(Ok(())) => None,
+				Some(Err(err)) => Some(err),
+				None => Some(app_error!("Loadable {} was not loaded", member.id())),
+			})
+			.collect::<Vec<_>>();
+		match errs.is_empty() {
+			true => Ok(()),
+			false => Err(AppError::from_multiple(errs)),
+		}
+	}
+
+	/// Returns all alive members
+	fn members(&self) -> Vec<Arc<dyn GroupMember>> {
+		self.entries
+			.lock()
+			.iter()
+			.filter_map(|entry| entry.member.upgrade())
+			.collect()
+	}
+}
+
+impl Default for LoadGroup {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Drop for LoadGroup {
+	fn drop(&mut self) {
+		self.cancel();
+	}
+}
+
+impl fmt::Debug for LoadGroup {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("LoadGroup")
+			.field("progress", &self.progress())
+			.finish_non_exhaustive()
+	}
+}
+
+/// Aggregate progress of a [`LoadGroup`]
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct GroupProgress {
+	/// Total number of loadables
+	pub total: usize,
+
+	/// Number of loadables loading
+	pub loading: usize,
+
+	/// Number of loadables loaded successfully
+	pub loaded: usize,
+
+	/// Number of loadables that failed to load
+	pub failed: usize,
+}
+
+impl GroupProgress {
+	/// Returns the number of loadables that finished loading
+	#[must_use]
+	pub const fn finished(&self) -> usize {
+		self.loaded + self.failed
+	}
+
+	/// Returns the fraction of loadables that finished loading.
+	///
+	/// An empty group is considered finished.
+	#[must_use]
+	pub fn fraction(&self) -> f32 {
+		#![expect(clippy::cast_precision_loss, reason = "We only need an approximate fraction")]
+
+		match self.total {
+			0 => 1.0,
+			total => self.finished() as f32 / total as f32,
+		}
+	}
+}
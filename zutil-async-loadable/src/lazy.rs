@@ -0,0 +1,133 @@
+//! Lazy loadable
+
+// Imports
+use {
+	crate::{AsyncLoadable, LoadHandle, ProgressUpdater},
+	app_error::AppError,
+	std::{fmt, ops::Deref, pin::Pin, sync::Arc},
+};
+
+/// Loader
+pub type Loader<T, P> =
+	dyn Fn(ProgressUpdater<P>) -> Pin<Box<dyn Future<Output = Result<T, AppError>> + Send>> + Send + Sync;
+
+/// An async loadable value that knows how to load itself.
+///
+/// Stores it's loader, so that it may be loaded without the caller
+/// needing to know how to load it.
+///
+/// To use in a `static`, wrap it in a [`LazyLock`](std::sync::LazyLock).
+pub struct AsyncLazy<T, P: 'static = !> {
+	/// Loadable
+	loadable: AsyncLoadable<T, P>,
+
+	/// Loader
+	loader: Arc<Loader<T, P>>,
+}
+
+impl<T, P: 'static> AsyncLazy<T, P> {
+	/// Creates a new, unloaded, lazy value
+	pub fn new<F, Fut>(f: F) -> Self
+	where
+		F: Fn(ProgressUpdater<P>) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<T, AppError>> + Send + 'static,
+	{
+		Self::from_loadable(AsyncLoadable::new(), f)
+	}
+
+	/// Creates a lazy value from an existing loadable
+	pub fn from_loadable<F, Fut>(loadable: AsyncLoadable<T, P>, f: F) -> Self
+	where
+		F: Fn(ProgressUpdater<P>) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<T, AppError>> + Send + 'static,
+	{
+		Self {
+			loadable,
+			loader: Arc::new(move |progress| Box::pin(f(progress))),
+		}
+	}
+
+	/// Clone this lazy value by reference-count.
+	///
+	/// The returned value shares the same state and loader as this one
+	#[must_use]
+	pub fn clone_rc(&self) -> Self {
+		Self {
+			loadable: self.loadable.clone_rc(),
+			loader:   Arc::clone(&self.loader),
+		}
+	}
+
+	/// Returns the inner loadable
+	#[must_use]
+	pub const fn loadable(&self) -> &AsyncLoadable<T, P> {
+		&self.loadable
+	}
+
+	/// Tries to load this value and returns a handle to get the value.
+	///
+	/// See [`AsyncLoadable::try_load`] for details.
+	#[expect(clippy::must_use_candidate, reason = "The caller might only want to start loading")]
+	pub fn try_load(&self) -> Option<LoadHandle<T>>
+	where
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		self.loadable.try_load(|progress| (self.loader)(progress))
+	}
+
+	/// Tries to reload this value and returns a handle to get the value.
+	///
+	/// While reloading, this value will be unloaded.
+	///
+	/// If already loading, returns `None`.
+	#[expect(clippy::must_use_candidate, reason = "The caller might only want to start loading")]
+	pub fn try_reload(&self) -> Option<LoadHandle<T>>
+	where
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		self.loadable.try_reload_discard(|progress| (self.loader)(progress))
+	}
+
+	/// Gets the value, loading it if not yet loaded.
+	///
+	/// If already loading, waits for it to load.
+	pub async fn get_or_load(&self) -> Result<T, AppError>
+	where
+		T: Clone + Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		self.loadable.try_load_or_wait(|progress| (self.loader)(progress)).await
+	}
+
+	/// Reloads the value.
+	///
+	/// If already loading, waits for it to load instead.
+	pub async fn reload(&self) -> Result<T, AppError>
+	where
+		T: Clone + Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		match self.try_reload() {
+			Some(load_handle) => load_handle.await,
+			None => self.loadable.wait().await,
+		}
+	}
+}
+
+impl<T, P: 'static> Deref for AsyncLazy<T, P> {
+	type Target = AsyncLoadable<T, P>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.loadable
+	}
+}
+
+impl<T: fmt::Debug, P: fmt::Debug + 'static> fmt::Debug for AsyncLazy<T, P> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("AsyncLazy")
+			.field("loadable", &self.loadable)
+			.finish_non_exhaustive()
+	}
+}
@@ -6,43 +6,153 @@
 //! It allows loading a value by spawning a [`tokio`] task, and allowing
 //! progress communication from the loader. At the end, the value is available
 //! from the type.
+//!
+//! Values and progress are published without locking, so readers never block.
+//! This requires the progress to be `Sync`, and values to be `Clone` whenever
+//! they need to be taken out of the loadable (e.g. [`AsyncLoadable::reset`]),
+//! since readers might still be holding onto them.
 
 // Features
 #![feature(async_fn_traits, type_alias_impl_trait, never_type)]
 
 // Modules
+mod budget;
+mod callback;
+mod cleanup;
+mod dependency;
+mod fallback;
+mod group;
+mod lazy;
 mod load_handle;
+mod local;
 mod progress;
-mod res_arc_guard;
+mod registry;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod stream;
+mod task;
+#[cfg(feature = "egui")]
+mod widget;
 
 // Exports
+#[cfg(feature = "serde")]
+pub use self::snapshot::Snapshot;
+#[cfg(feature = "egui")]
+pub use self::widget::EguiProgress;
 pub use self::{
+	budget::MemoryBudget,
+	callback::CallbackHandle,
+	dependency::{DependencyGraph, DependencyGraphNode, LoadableId},
+	fallback::{Fallback, WithFallback},
+	group::{GroupProgress, LoadGroup},
+	lazy::AsyncLazy,
 	load_handle::{LoadHandle, LoadHandleFut},
+	local::{LocalAsyncLoadable, LocalLoadHandle, LocalLoadHandleFut, LocalProgressUpdater},
 	progress::ProgressUpdater,
+	registry::{LoadableInfo, LoadableState, RegistryDump},
+	stream::{AsyncStreamLoadable, StreamStatus, StreamWriter},
 };
 
 // Imports
 use {
-	self::res_arc_guard::ResArcGuard,
+	self::{
+		callback::{Callbacks, OnLoaded},
+		cleanup::{CleanupGuard, Cleanups},
+		dependency::Deps,
+		fallback::FallbackState,
+		task::{LoadState, Task},
+	},
 	app_error::AppError,
-	parking_lot::Mutex,
-	std::{self, error::Error, fmt, ops::AsyncFnOnce, sync::Arc},
-	tokio::{sync::Notify, task},
+	arc_swap::ArcSwapOption,
+	parking_lot::{Mutex, MutexGuard},
+	std::{
+		self,
+		error::Error,
+		fmt,
+		ops::AsyncFnOnce,
+		sync::{
+			Arc,
+			atomic::{self, AtomicU64},
+		},
+	},
+	tokio::sync::Notify,
 };
 
 /// Inner
 pub(crate) struct Inner<T, P> {
-	/// Result
-	res: Mutex<Res<T>>,
+	/// Id
+	id: LoadableId,
 
-	/// Progress
-	progress: Mutex<Option<P>>,
+	/// Result
+	res: ArcSwapOption<Result<T, AppError>>,
 
-	/// Task handle
-	task_handle: Mutex<Option<task::AbortHandle>>,
+	/// Load state
+	load: LoadState<P>,
 
 	/// Wait
 	wait: Notify,
+
+	/// Dependencies
+	deps: Mutex<Deps>,
+
+	/// Loaded callbacks
+	on_loaded: Callbacks<OnLoaded<T>>,
+
+	/// Fallback
+	fallback: FallbackState<T>,
+
+	/// Last access, if tracked by a memory budget.
+	///
+	/// Zero while untracked.
+	last_access: AtomicU64,
+}
+
+impl<T, P> Inner<T, P> {
+	/// Records an access of the value, if tracked by a memory budget
+	fn touch(&self) {
+		if self.last_access.load(atomic::Ordering::Relaxed) != 0 {
+			self.last_access.store(budget::tick(), atomic::Ordering::Relaxed);
+		}
+	}
+
+	/// Stops loading, if loading
+	fn stop_loading(&self) {
+		let mut task = self.load.task.lock();
+		if task.is_running() {
+			task.stop();
+			self.load.progress.store(None);
+			drop(task);
+			self.load.changed();
+		}
+	}
+}
+
+/// Marks a loader task as finished on drop.
+///
+/// Moved into the loader task, so that anyone waiting on changes is notified
+/// even if the loader panics or is aborted.
+struct FinishGuard<T, P> {
+	/// Inner
+	inner: Arc<Inner<T, P>>,
+
+	/// Generation of the task
+	generation: u64,
+}
+
+impl<T, P> Drop for FinishGuard<T, P> {
+	fn drop(&mut self) {
+		// Note: If the task finished normally, or a newer load was started
+		//       (or it was stopped), it's already been handled.
+		let mut task = self.inner.load.task.lock();
+		if task.generation() != self.generation || !task.is_running() {
+			return;
+		}
+		task.set_finished();
+		self.inner.load.progress.store(None);
+		drop(task);
+
+		self.inner.load.changed();
+	}
 }
 
 /// An async fallible loadable value.
@@ -73,13 +183,22 @@ impl<T, P> AsyncLoadable<T, P> {
 	}
 
 	/// Creates a loadable from it's result
-	pub(crate) fn from_res(res: Res<T>) -> Self {
+	pub(crate) fn from_res(res: Option<Result<T, AppError>>) -> Self {
+		let load = LoadState::new();
+		if res.is_some() {
+			load.task.lock().set_loaded();
+		}
+
 		Self {
 			inner: Arc::new(Inner {
-				res:         Mutex::new(res),
-				progress:    Mutex::new(None),
-				task_handle: Mutex::new(None),
-				wait:        Notify::new(),
+				id: LoadableId::new(),
+				res: ArcSwapOption::new(res.map(Arc::new)),
+				load,
+				wait: Notify::new(),
+				deps: Mutex::new(Deps::default()),
+				on_loaded: Callbacks::new(),
+				last_access: AtomicU64::new(0),
+				fallback: FallbackState::new(),
 			}),
 		}
 	}
@@ -95,12 +214,24 @@ impl<T, P> AsyncLoadable<T, P> {
 	}
 
 	/// Gets the value of the loadable.
+	///
+	/// This never blocks.
 	#[must_use]
 	pub fn get(&self) -> Option<Result<T, AppError>>
 	where
 		T: Clone,
 	{
-		self.inner.res.lock().clone()
+		self.inner.touch();
+		self.inner.res.load().as_deref().cloned()
+	}
+
+	/// Gets the value of the loadable, without cloning it.
+	///
+	/// This never blocks.
+	#[must_use]
+	pub fn get_arc(&self) -> Option<Arc<Result<T, AppError>>> {
+		self.inner.touch();
+		self.inner.res.load_full()
 	}
 
 	/// Waits for this loadable to load
@@ -112,64 +243,88 @@ impl<T, P> AsyncLoadable<T, P> {
 	where
 		T: Clone,
 	{
-		#![expect(clippy::await_holding_lock, reason = "We drop the lock before `await`ing")]
+		#![expect(
+			clippy::future_not_send,
+			reason = "The future is `Send` when `T` and `P` are `Send + Sync`"
+		)]
 
-		let mut res = self.inner.res.lock();
 		loop {
-			match &*res {
-				Some(res) => break res.clone(),
-				None => {
-					// Get the wait future.
-					// Note: According to the documentation, we do *not* need to
-					//       poll it once before being added to the queue for `notify_waiters`,
-					//       which we use.
-					let wait_fut = self.inner.wait.notified();
-
-					// Then await the future without the lock
-					drop(res);
-					wait_fut.await;
-					res = self.inner.res.lock();
-				},
+			// Get the wait future.
+			// Note: According to the documentation, we do *not* need to
+			//       poll it once before being added to the queue for `notify_waiters`,
+			//       which we use.
+			let wait_fut = self.inner.wait.notified();
+
+			// Then check if we're loaded before awaiting it.
+			if let Some(res) = &*self.inner.res.load() {
+				self.inner.touch();
+				break Result::clone(res);
 			}
+			wait_fut.await;
 		}
 	}
 
 	/// Resets the currently loaded value.
 	///
-	/// Returns the old value, if any.
+	/// Any dependents of this loadable are also reset.
+	///
+	/// Returns the old value, if any. The value is moved if nobody else is
+	/// holding onto it, otherwise it's cloned. Use [`Self::reset_arc`] to
+	/// avoid cloning.
 	#[must_use]
-	pub fn reset(&self) -> Option<Result<T, AppError>> {
-		self.inner.res.lock().take()
+	pub fn reset(&self) -> Option<Result<T, AppError>>
+	where
+		T: Clone,
+	{
+		self.reset_arc().map(Arc::unwrap_or_clone)
+	}
+
+	/// Resets the currently loaded value, without cloning it.
+	///
+	/// See [`Self::reset`] for details.
+	#[must_use]
+	pub fn reset_arc(&self) -> Option<Arc<Result<T, AppError>>> {
+		let res = self.inner.res.swap(None);
+		self.inner.fallback.clear();
+		self.inner.load.changed();
+		dependency::invalidate_dependents(self.inner.id, &self.inner.deps);
+
+		res
 	}
 
 	/// Gets the progress of the loadable.
 	///
-	/// If the progress is currently being updated, returns `None`
+	/// This never blocks.
 	#[must_use]
 	pub fn progress(&self) -> Option<P>
 	where
 		P: Clone,
 	{
-		self.inner.progress.try_lock().as_deref().cloned().flatten()
+		self.inner.load.progress.load().as_deref().cloned()
 	}
 
 	/// Returns if the value is loading.
 	#[must_use]
 	pub fn is_loading(&self) -> bool {
-		self.inner
-			.task_handle
-			.lock()
-			.as_ref()
-			.is_some_and(|task| !task.is_finished())
+		self.inner.load.task.lock().is_running()
+	}
+
+	/// Returns the generation of the latest load.
+	///
+	/// This is incremented whenever a load is started or stopped.
+	#[must_use]
+	pub fn generation(&self) -> u64 {
+		self.inner.load.task.lock().generation()
 	}
 
 	/// Stops the loading value.
 	///
+	/// Even if the loader isn't aborted immediately, it's result and
+	/// progress will be discarded.
+	///
 	/// If not loading, does nothing
 	pub fn stop_loading(&self) {
-		if let Some(task_handle) = &*self.inner.task_handle.lock() {
-			task_handle.abort();
-		}
+		self.inner.stop_loading();
 	}
 
 	/// Tries to load this value and returns a handle to get the value.
@@ -182,57 +337,206 @@ impl<T, P> AsyncLoadable<T, P> {
 		F: AsyncFnOnce(ProgressUpdater<P>) -> Result<T, AppError>,
 		F::CallOnceFuture: Send + 'static,
 		T: Send + Sync + 'static,
-		P: Send + 'static,
+		P: Send + Sync + 'static,
 	{
 		// If we're already loading and the task isn't finished, return
-		let mut task_handle = self.inner.task_handle.lock();
-		if task_handle
-			.as_ref()
-			.is_some_and(|task_handle| !task_handle.is_finished())
-		{
+		let task = self.inner.load.task.lock();
+		if task.is_running() {
 			return None;
 		}
 
 		// If we're already initialized, return it
-		if let res = ResArcGuard::new(Arc::clone(&self.inner)) &&
-			res.get().is_some()
-		{
+		if let Some(res) = self.inner.res.load_full() {
+			self.inner.touch();
 			return Some(LoadHandle::from_loaded(res));
 		}
 
 		// Otherwise start a task and return.
-		let progress_updater = ProgressUpdater::new(Arc::clone(&self.inner));
+		Some(self.start_loader(task, |progress| f(progress), false))
+	}
+
+	/// Tries to reload this value and returns a handle to get the value.
+	///
+	/// The loader receives the previous value, if any, which is taken
+	/// out of this loadable. This means that while reloading, this loadable
+	/// will be unloaded.
+	///
+	/// The previous value is moved if nobody else is holding onto it (e.g. a
+	/// [`LoadHandle`] that wasn't awaited yet), otherwise it's cloned.
+	///
+	/// If already loading, returns `None`.
+	pub fn try_reload<F>(&self, f: F) -> Option<LoadHandle<T>>
+	where
+		F: AsyncFnOnce(Option<Result<T, AppError>>, ProgressUpdater<P>) -> Result<T, AppError>,
+		F::CallOnceFuture: Send + 'static,
+		T: Clone + Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		// If we're already loading and the task isn't finished, return
+		let task = self.inner.load.task.lock();
+		if task.is_running() {
+			return None;
+		}
+
+		// Otherwise take the previous value and start a task
+		let prev = self.inner.res.swap(None).map(Arc::unwrap_or_clone);
+		Some(self.start_loader(task, |progress| f(prev, progress), true))
+	}
+
+	/// Tries to reload this value, discarding the previous value.
+	///
+	/// If already loading, returns `None`.
+	pub(crate) fn try_reload_discard<F>(&self, f: F) -> Option<LoadHandle<T>>
+	where
+		F: AsyncFnOnce(ProgressUpdater<P>) -> Result<T, AppError>,
+		F::CallOnceFuture: Send + 'static,
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		// If we're already loading and the task isn't finished, return
+		let task = self.inner.load.task.lock();
+		if task.is_running() {
+			return None;
+		}
+
+		// Otherwise remove the previous value and start a task
+		self.inner.res.store(None);
+		Some(self.start_loader(task, |progress| f(progress), true))
+	}
+
+	/// Tries to reload this value and returns a handle to get the value.
+	///
+	/// The loader receives a clone of the previous value, if any. Unlike
+	/// [`Self::try_reload`], the previous value is kept while reloading.
+	///
+	/// If already loading, returns `None`.
+	pub fn try_reload_cloned<F>(&self, f: F) -> Option<LoadHandle<T>>
+	where
+		F: AsyncFnOnce(Option<Result<T, AppError>>, ProgressUpdater<P>) -> Result<T, AppError>,
+		F::CallOnceFuture: Send + 'static,
+		T: Clone + Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		// If we're already loading and the task isn't finished, return
+		let task = self.inner.load.task.lock();
+		if task.is_running() {
+			return None;
+		}
+
+		// Otherwise clone the previous value and start a task
+		let prev = self.inner.res.load().as_deref().cloned();
+		Some(self.start_loader(task, |progress| f(prev, progress), true))
+	}
+
+	/// Forces a reload of this value and returns a handle to get the value.
+	///
+	/// If already loading, the current loader is stopped and it's result
+	/// discarded, see [`Self::stop_loading`].
+	///
+	/// Unlike [`Self::try_reload`], the previous value is kept while reloading.
+	pub fn force_reload<F>(&self, f: F) -> LoadHandle<T>
+	where
+		F: AsyncFnOnce(ProgressUpdater<P>) -> Result<T, AppError>,
+		F::CallOnceFuture: Send + 'static,
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let task = self.inner.load.task.lock();
+		self.inner.load.progress.store(None);
+		self.start_loader(task, |progress| f(progress), true)
+	}
+
+	/// Starts a loader task for a new generation.
+	///
+	/// The loader future is only created after `task` is unlocked, so that
+	/// the loader may use it's progress updater (or this loadable) before
+	/// it's first await point.
+	fn start_loader<F, Fut>(&self, mut task: MutexGuard<'_, Task>, f: F, is_reload: bool) -> LoadHandle<T>
+	where
+		F: FnOnce(ProgressUpdater<P>) -> Fut,
+		Fut: Future<Output = Result<T, AppError>> + Send + 'static,
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let generation = task.start();
+		drop(task);
+
+		let progress_updater = ProgressUpdater::new(Arc::clone(&self.inner), generation);
+		let cleanups = progress_updater.cleanups();
 		let fut = f(progress_updater);
+		self.spawn_loader(generation, fut, cleanups, is_reload)
+	}
+
+	/// Spawns a loader task for generation `generation`.
+	///
+	/// If `is_reload`, or if a value was replaced, our dependents will be
+	/// invalidated after loading.
+	///
+	/// Unless the load succeeds, `cleanups` are run afterwards.
+	fn spawn_loader<Fut>(&self, generation: u64, fut: Fut, cleanups: Arc<Cleanups>, is_reload: bool) -> LoadHandle<T>
+	where
+		Fut: Future<Output = Result<T, AppError>> + Send + 'static,
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
 		let join_handle = tokio::spawn({
 			let inner = Arc::clone(&self.inner);
+			let cleanup_guard = CleanupGuard::new(cleanups);
+			let finish_guard = FinishGuard {
+				inner: Arc::clone(&self.inner),
+				generation,
+			};
 			async move {
+				let _finish_guard = finish_guard;
+
 				// Wait for the result
 				// TODO: Should we catch panics here? Tokio will catch them anyway, but it
 				//       might be cleaner if we also catch them and write them to the
 				//       result.
-				let res = fut.await;
-
-				// Write the result
-				let mut inner_res = ResArcGuard::new(Arc::clone(&inner));
-				inner_res.with_mut(|inner_res| *inner_res = Some(res));
-
-				// Remove the progress
-				// Note: This can't deadlock, as the progress updater already exited.
-				// TODO: It *might* be possible to deadlock, if `P`'s clone impl waits
-				//       for the value to be loaded via `LoadHandle`, verify.
-				*inner.progress.lock() = None;
+				let res = Arc::new(fut.await);
+
+				// Write the result and remove the progress, if we're still the latest load.
+				// Note: We keep the task locked so a new load can't start meanwhile.
+				let mut task = inner.load.task.lock();
+				if task.generation() != generation {
+					return None;
+				}
+				let prev_res = inner.res.swap(Some(Arc::clone(&res)));
+				task.set_loaded();
+				inner.fallback.loaded(&res);
+				if res.is_ok() {
+					cleanup_guard.disarm();
+				}
+				inner.load.progress.store(None);
+				drop(task);
+
+				// If we reloaded, invalidate our dependents.
+				// Note: We do this before waking anyone up, so they never see
+				//       our new value alongside stale dependents.
+				if is_reload || prev_res.is_some() {
+					dependency::invalidate_dependents(inner.id, &inner.deps);
+				}
 
 				// Then wake up anyone waiting for us.
 				inner.wait.notify_waiters();
+				inner.on_loaded.call_all(|f| f(&res));
+				inner.load.changed();
 
-				// Then hand the inner lock to the join handle
-				inner_res
+				// Then hand the result to the join handle
+				Some(res)
 			}
 		});
-		*task_handle = Some(join_handle.abort_handle());
 
+		// Note: If a newer load was started while creating the future, we're
+		//       already superseded, so just abort ourselves.
+		let mut task = self.inner.load.task.lock();
+		match task.generation() == generation {
+			true => task.set_handle(join_handle.abort_handle()),
+			false => join_handle.abort(),
+		}
+		drop(task);
 
-		Some(LoadHandle::from_task(join_handle))
+		LoadHandle::from_task(join_handle)
 	}
 
 	/// Tries to load this value, or waits for it to be loaded.
@@ -241,7 +545,7 @@ impl<T, P> AsyncLoadable<T, P> {
 		F: AsyncFnOnce(ProgressUpdater<P>) -> Result<T, AppError>,
 		F::CallOnceFuture: Send + 'static,
 		T: Clone + Send + Sync + 'static,
-		P: Send + 'static,
+		P: Send + Sync + 'static,
 	{
 		// Try to load it.
 		match self.try_load(f) {
@@ -263,22 +567,15 @@ impl<T: fmt::Debug, P: fmt::Debug> fmt::Debug for AsyncLoadable<T, P> {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		let mut f = f.debug_struct("AsyncLoadable");
 
-		// Try to lock each field to output it
+		f.field("value", &*self.inner.res.load());
+		f.field("progress", &*self.inner.load.progress.load());
+
+		// Try to lock the task to output it
 		let mut any_missing = false;
-		match self.inner.res.try_lock() {
-			Some(res) => _ = f.field("value", &*res),
-			None => any_missing = true,
-		}
-		match self.inner.progress.try_lock() {
-			Some(progress) => _ = f.field("progress", &*progress),
-			None => any_missing = true,
-		}
-		match self.inner.task_handle.try_lock() {
-			Some(task_handle) => {
-				let is_loading = task_handle
-					.as_ref()
-					.is_some_and(|task_handle| !task_handle.is_finished());
-				f.field("is_loading", &is_loading);
+		match self.inner.load.task.try_lock() {
+			Some(task) => {
+				f.field("is_loading", &task.is_running());
+				f.field("generation", &task.generation());
 			},
 			None => any_missing = true,
 		}
@@ -289,6 +586,3 @@ impl<T: fmt::Debug, P: fmt::Debug> fmt::Debug for AsyncLoadable<T, P> {
 		}
 	}
 }
-
-/// Result type
-type Res<T> = Option<Result<T, AppError>>;
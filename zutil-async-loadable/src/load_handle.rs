@@ -2,11 +2,11 @@
 
 // Imports
 use {
-	crate::res_arc_guard::ResArcGuard,
 	app_error::{AppError, app_error},
 	std::{
 		future::{Future, IntoFuture},
 		pin::Pin,
+		sync::Arc,
 		task::Poll,
 	},
 	tokio::task,
@@ -15,10 +15,10 @@ use {
 /// Load handle inner
 enum LoaderHandleInner<T: 'static> {
 	/// Task
-	Task(task::JoinHandle<ResArcGuard<T>>),
+	Task(task::JoinHandle<Option<Arc<Result<T, AppError>>>>),
 
 	/// Already loaded
-	Loaded(ResArcGuard<T>),
+	Loaded(Arc<Result<T, AppError>>),
 }
 
 /// Load handle
@@ -40,12 +40,12 @@ impl<T> LoadHandle<T> {
 	}
 
 	/// Creates a loader handle from a task
-	pub(crate) const fn from_task(task: task::JoinHandle<ResArcGuard<T>>) -> Self {
+	pub(crate) const fn from_task(task: task::JoinHandle<Option<Arc<Result<T, AppError>>>>) -> Self {
 		Self::new(LoaderHandleInner::Task(task))
 	}
 
 	/// Creates a loader handle from a loaded value
-	pub(crate) const fn from_loaded(res: ResArcGuard<T>) -> Self {
+	pub(crate) const fn from_loaded(res: Arc<Result<T, AppError>>) -> Self {
 		Self::new(LoaderHandleInner::Loaded(res))
 	}
 
@@ -60,9 +60,9 @@ impl<T> LoadHandle<T> {
 }
 
 /// Abort task on drop
-struct AbortTaskOnDrop {
+pub struct AbortTaskOnDrop {
 	/// Task handle
-	task_handle: task::AbortHandle,
+	pub task_handle: task::AbortHandle,
 }
 
 impl Drop for AbortTaskOnDrop {
@@ -120,18 +120,20 @@ where
 		LoadHandleFut {
 			inner: {
 				async move {
-					// Get the lock to inner
-					let inner = match self.inner {
-						LoaderHandleInner::Task(join_handle) =>
-							join_handle.await.map_err(|err| match err.try_into_panic() {
+					// Get the result
+					let res = match self.inner {
+						LoaderHandleInner::Task(join_handle) => join_handle
+							.await
+							.map_err(|err| match err.try_into_panic() {
 								Ok(err) => app_error!("Loader panicked: {err:?}"),
 								Err(err) => AppError::new(&err).context("Loader was cancelled"),
-							})?,
-						LoaderHandleInner::Loaded(inner) => inner,
+							})?
+							.ok_or_else(|| app_error!("Loader was superseded by a newer load"))?,
+						LoaderHandleInner::Loaded(res) => res,
 					};
 
 					// Then get the value
-					inner.get().clone().expect("Value should be loaded")
+					Arc::unwrap_or_clone(res)
 				}
 			},
 			abort_on_drop,
@@ -0,0 +1,447 @@
+//! Thread-local loadable
+
+// Imports
+use {
+	crate::{load_handle::AbortTaskOnDrop, task::Task},
+	app_error::{AppError, app_error},
+	std::{cell::RefCell, error::Error, fmt, future::IntoFuture, ops::AsyncFnOnce, pin::Pin, rc::Rc, task::Poll},
+	tokio::{sync::Notify, task},
+};
+
+/// Local load state
+struct LocalLoadState<P> {
+	/// Task
+	task: RefCell<Task>,
+
+	/// Progress
+	progress: RefCell<Option<P>>,
+}
+
+/// Local inner
+struct LocalInner<T, P> {
+	/// Result
+	res: RefCell<Option<Rc<Result<T, AppError>>>>,
+
+	/// Load state
+	load: Rc<LocalLoadState<P>>,
+
+	/// Wait
+	wait: Notify,
+}
+
+/// A thread-local async fallible loadable value.
+///
+/// Like [`AsyncLoadable`](crate::AsyncLoadable), but for single-threaded
+/// runtimes, so neither the value nor the loader need to be `Send`.
+///
+/// Loaders are spawned with [`tokio::task::spawn_local`], so loading must
+/// happen within a [`LocalSet`](tokio::task::LocalSet) or a local runtime.
+pub struct LocalAsyncLoadable<T, P = !> {
+	/// Inner
+	inner: Rc<LocalInner<T, P>>,
+}
+
+impl<T, P> LocalAsyncLoadable<T, P> {
+	/// Creates a new, unloaded, value
+	#[must_use]
+	pub fn new() -> Self {
+		Self::from_res(None)
+	}
+
+	/// Creates a new, loaded, value
+	pub fn from_value(value: T) -> Self {
+		Self::from_res(Some(Ok(value)))
+	}
+
+	/// Creates a new, errored, value
+	pub fn from_error<E>(err: &E) -> Self
+	where
+		E: ?Sized + Error,
+	{
+		Self::from_res(Some(Err(AppError::new(err))))
+	}
+
+	/// Creates a loadable from it's result
+	fn from_res(res: Option<Result<T, AppError>>) -> Self {
+		Self {
+			inner: Rc::new(LocalInner {
+				res:  RefCell::new(res.map(Rc::new)),
+				load: Rc::new(LocalLoadState {
+					task:     RefCell::new(Task::default()),
+					progress: RefCell::new(None),
+				}),
+				wait: Notify::new(),
+			}),
+		}
+	}
+
+	/// Clone this async loadable by reference-count.
+	///
+	/// The returned loadable shares the same state as this one
+	#[must_use]
+	pub fn clone_rc(&self) -> Self {
+		Self {
+			inner: Rc::clone(&self.inner),
+		}
+	}
+
+	/// Gets the value of the loadable.
+	#[must_use]
+	pub fn get(&self) -> Option<Result<T, AppError>>
+	where
+		T: Clone,
+	{
+		self.inner.res.borrow().as_deref().cloned()
+	}
+
+	/// Gets the value of the loadable, without cloning it.
+	#[must_use]
+	pub fn get_rc(&self) -> Option<Rc<Result<T, AppError>>> {
+		self.inner.res.borrow().clone()
+	}
+
+	/// Waits for this loadable to load
+	pub async fn wait(&self) -> Result<T, AppError>
+	where
+		T: Clone,
+	{
+		#![expect(clippy::future_not_send, reason = "Local loadables are never `Send`")]
+
+		loop {
+			// Note: See `AsyncLoadable::wait` for why we get the future before checking.
+			let wait_fut = self.inner.wait.notified();
+			if let Some(res) = self.get() {
+				break res;
+			}
+			wait_fut.await;
+		}
+	}
+
+	/// Resets the currently loaded value.
+	///
+	/// Returns the old value, if any. The value is moved if nobody else is
+	/// holding onto it, otherwise it's cloned. Use [`Self::reset_rc`] to
+	/// avoid cloning.
+	#[must_use]
+	pub fn reset(&self) -> Option<Result<T, AppError>>
+	where
+		T: Clone,
+	{
+		self.reset_rc().map(Rc::unwrap_or_clone)
+	}
+
+	/// Resets the currently loaded value, without cloning it.
+	///
+	/// See [`Self::reset`] for details.
+	#[must_use]
+	pub fn reset_rc(&self) -> Option<Rc<Result<T, AppError>>> {
+		self.inner.res.borrow_mut().take()
+	}
+
+	/// Gets the progress of the loadable.
+	#[must_use]
+	pub fn progress(&self) -> Option<P>
+	where
+		P: Clone,
+	{
+		self.inner.load.progress.borrow().clone()
+	}
+
+	/// Returns if the value is loading.
+	#[must_use]
+	pub fn is_loading(&self) -> bool {
+		self.inner.load.task.borrow().is_running()
+	}
+
+	/// Returns the current load generation.
+	///
+	/// See [`AsyncLoadable::generation`](crate::AsyncLoadable::generation).
+	#[must_use]
+	pub fn generation(&self) -> u64 {
+		self.inner.load.task.borrow().generation()
+	}
+
+	/// Stops loading the value.
+	///
+	/// If not loading, does nothing
+	pub fn stop_loading(&self) {
+		let mut task = self.inner.load.task.borrow_mut();
+		if task.is_running() {
+			task.stop();
+			drop(task);
+			*self.inner.load.progress.borrow_mut() = None;
+		}
+	}
+
+	/// Tries to load this value and returns a handle to get the value.
+	///
+	/// If already loading, returns `None`.
+	///
+	/// # Panics
+	/// Panics if not called within a [`LocalSet`](tokio::task::LocalSet) or
+	/// a local runtime.
+	pub fn try_load<F>(&self, f: F) -> Option<LocalLoadHandle<T>>
+	where
+		F: AsyncFnOnce(LocalProgressUpdater<P>) -> Result<T, AppError>,
+		F::CallOnceFuture: 'static,
+		T: 'static,
+		P: 'static,
+	{
+		let mut task = self.inner.load.task.borrow_mut();
+		if task.is_running() {
+			return None;
+		}
+
+		// If we're already initialized, return it
+		if let Some(res) = self.get_rc() {
+			return Some(LocalLoadHandle::new(LocalLoadHandleInner::Loaded(res)));
+		}
+
+		// Otherwise start a task and return.
+		// Note: We create the future without the task borrowed, so that
+		//       the loader may use it's progress updater (or this loadable)
+		//       before it's first await point.
+		let generation = task.start();
+		drop(task);
+		let fut = f(LocalProgressUpdater {
+			state: Rc::clone(&self.inner.load),
+			generation,
+		});
+		let join_handle = task::spawn_local({
+			let inner = Rc::clone(&self.inner);
+			async move {
+				let res = Rc::new(fut.await);
+
+				// Write the result and remove the progress, if we're still the latest load.
+				let mut task = inner.load.task.borrow_mut();
+				if task.generation() != generation {
+					return None;
+				}
+				task.set_loaded();
+				drop(task);
+				*inner.res.borrow_mut() = Some(Rc::clone(&res));
+				*inner.load.progress.borrow_mut() = None;
+
+				inner.wait.notify_waiters();
+				Some(res)
+			}
+		});
+
+		// Note: If a newer load was started while creating the future, we're
+		//       already superseded, so just abort ourselves.
+		let mut task = self.inner.load.task.borrow_mut();
+		match task.generation() == generation {
+			true => task.set_handle(join_handle.abort_handle()),
+			false => join_handle.abort(),
+		}
+		drop(task);
+
+		Some(LocalLoadHandle::new(LocalLoadHandleInner::Task(join_handle)))
+	}
+
+	/// Tries to load this value, or waits for it to be loaded.
+	///
+	/// # Panics
+	/// See [`Self::try_load`].
+	pub async fn try_load_or_wait<F>(&self, f: F) -> Result<T, AppError>
+	where
+		F: AsyncFnOnce(LocalProgressUpdater<P>) -> Result<T, AppError>,
+		F::CallOnceFuture: 'static,
+		T: Clone + 'static,
+		P: 'static,
+	{
+		#![expect(clippy::future_not_send, reason = "Local loadables are never `Send`")]
+
+		match self.try_load(f) {
+			Some(load_handle) => load_handle.await,
+			None => self.wait().await,
+		}
+	}
+}
+
+impl<T, P> Default for LocalAsyncLoadable<T, P> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: fmt::Debug, P: fmt::Debug> fmt::Debug for LocalAsyncLoadable<T, P> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut f = f.debug_struct("LocalAsyncLoadable");
+
+		// Try to borrow everything to output it
+		let mut any_missing = false;
+		match self.inner.res.try_borrow() {
+			Ok(res) => _ = f.field("value", &*res),
+			Err(_) => any_missing = true,
+		}
+		match self.inner.load.progress.try_borrow() {
+			Ok(progress) => _ = f.field("progress", &*progress),
+			Err(_) => any_missing = true,
+		}
+		match self.inner.load.task.try_borrow() {
+			Ok(task) => {
+				f.field("is_loading", &task.is_running());
+				f.field("generation", &task.generation());
+			},
+			Err(_) => any_missing = true,
+		}
+
+		match any_missing {
+			true => f.finish_non_exhaustive(),
+			false => f.finish(),
+		}
+	}
+}
+
+/// Local progress updater
+pub struct LocalProgressUpdater<P> {
+	/// Load state
+	state: Rc<LocalLoadState<P>>,
+
+	/// Generation of the load we're updating
+	generation: u64,
+}
+
+impl<P> LocalProgressUpdater<P> {
+	/// Updates the progress
+	pub fn update(&self, progress: P) {
+		if self.state.task.borrow().generation() == self.generation {
+			*self.state.progress.borrow_mut() = Some(progress);
+		}
+	}
+
+	/// Updates the progress.
+	///
+	/// While `f` runs, the progress is taken out of the loadable.
+	pub fn update_with<F>(&self, f: F)
+	where
+		F: FnOnce(&mut P),
+		P: Default,
+	{
+		if self.state.task.borrow().generation() != self.generation {
+			return;
+		}
+
+		// Note: We don't keep the progress borrowed while calling `f`, since
+		//       it might access the loadable.
+		let mut progress = self.state.progress.take().unwrap_or_default();
+		f(&mut progress);
+		if self.state.task.borrow().generation() == self.generation {
+			*self.state.progress.borrow_mut() = Some(progress);
+		}
+	}
+}
+
+/// Local load handle inner
+enum LocalLoadHandleInner<T> {
+	/// Task
+	Task(task::JoinHandle<Option<Rc<Result<T, AppError>>>>),
+
+	/// Already loaded
+	Loaded(Rc<Result<T, AppError>>),
+}
+
+/// Local load handle
+pub struct LocalLoadHandle<T> {
+	/// Inner
+	inner: LocalLoadHandleInner<T>,
+
+	/// Whether to abort the loading when this handle's future is cancelled
+	abort_on_drop: bool,
+}
+
+impl<T> LocalLoadHandle<T> {
+	/// Creates the loader handle
+	const fn new(inner: LocalLoadHandleInner<T>) -> Self {
+		Self {
+			inner,
+			abort_on_drop: true,
+		}
+	}
+
+	/// Sets whether the inner task should be aborted if this handle's
+	/// future is dropped.
+	///
+	/// By default, this is `true`
+	#[must_use]
+	pub fn with_abort_on_drop(self, abort_on_drop: bool) -> Self {
+		Self { abort_on_drop, ..self }
+	}
+}
+
+impl<T> IntoFuture for LocalLoadHandle<T>
+where
+	T: Clone + 'static,
+{
+	type IntoFuture = LocalLoadHandleFut<T>;
+	type Output = Result<T, AppError>;
+
+	#[define_opaque(LocalLoadHandleFutInner)]
+	fn into_future(self) -> Self::IntoFuture {
+		let abort_on_drop = match self.abort_on_drop {
+			true => match &self.inner {
+				LocalLoadHandleInner::Task(join_handle) => Some(AbortTaskOnDrop {
+					task_handle: join_handle.abort_handle(),
+				}),
+				LocalLoadHandleInner::Loaded(_) => None,
+			},
+			false => None,
+		};
+
+		LocalLoadHandleFut {
+			inner: {
+				async move {
+					// Get the result
+					let res = match self.inner {
+						LocalLoadHandleInner::Task(join_handle) => join_handle
+							.await
+							.map_err(|err| match err.try_into_panic() {
+								Ok(err) => app_error!("Loader panicked: {err:?}"),
+								Err(err) => AppError::new(&err).context("Loader was cancelled"),
+							})?
+							.ok_or_else(|| app_error!("Loader was superseded by a newer load"))?,
+						LocalLoadHandleInner::Loaded(res) => res,
+					};
+
+					// Then get the value
+					Rc::unwrap_or_clone(res)
+				}
+			},
+			abort_on_drop,
+		}
+	}
+}
+
+/// Local load handle future
+#[pin_project::pin_project]
+pub struct LocalLoadHandleFut<T>
+where
+	T: Clone + 'static,
+{
+	/// Inner future
+	#[pin]
+	inner: LocalLoadHandleFutInner<T>,
+
+	/// Abort on drop.
+	// Note: It's fine to unconditionally drop this, even after the task
+	//       is completed, since that will just do nothing.
+	abort_on_drop: Option<AbortTaskOnDrop>,
+}
+
+impl<T> Future for LocalLoadHandleFut<T>
+where
+	T: Clone,
+{
+	type Output = Result<T, AppError>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+		self.project().inner.poll(cx)
+	}
+}
+
+/// The inner future
+pub type LocalLoadHandleFutInner<T>
+where
+	T: Clone + 'static,
+= impl Future<Output = Result<T, AppError>>;
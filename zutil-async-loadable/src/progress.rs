@@ -1,39 +1,87 @@
 //! Progress updater
 
 // Imports
-use {crate::Inner, mappable_rc::Marc, parking_lot::Mutex, std::sync::Arc};
+use {
+	crate::{Inner, cleanup::Cleanups, task::LoadState},
+	mappable_rc::Marc,
+	std::sync::Arc,
+};
 
 /// Progress updater
 pub struct ProgressUpdater<P: 'static> {
-	/// Progress
-	progress: Marc<Mutex<Option<P>>>,
+	/// Load state
+	state: Marc<LoadState<P>>,
+
+	/// Generation of the load we're updating
+	generation: u64,
+
+	/// Cleanup actions of the load
+	cleanups: Arc<Cleanups>,
 }
 
 impl<P> ProgressUpdater<P> {
 	/// Creates a new progress updater
-	pub(crate) fn new<T>(inner: Arc<Inner<T, P>>) -> Self
+	pub(crate) fn new<T>(inner: Arc<Inner<T, P>>, generation: u64) -> Self
 	where
-		T: Send + 'static,
-		P: Send,
+		T: Send + Sync + 'static,
+		P: Send + Sync,
 	{
 		let inner = Marc::from_arc(inner);
-		let progress = Marc::map(inner, |inner| &inner.progress);
-		Self { progress }
+		let state = Marc::map(inner, |inner| &inner.load);
+		Self {
+			state,
+			generation,
+			cleanups: Arc::new(Cleanups::default()),
+		}
+	}
+
+	/// Returns the cleanup actions of the load
+	pub(crate) fn cleanups(&self) -> Arc<Cleanups> {
+		Arc::clone(&self.cleanups)
+	}
+
+	/// Registers a cleanup action.
+	///
+	/// If the load is aborted, fails, or is superseded by a newer load,
+	/// all cleanup actions are run in a separate task, in reverse order of
+	/// registration.
+	///
+	/// The actions are cancelled if they don't finish within 10 seconds, unless
+	/// the runtime doesn't have timers enabled.
+	pub fn on_cleanup<F>(&self, f: F)
+	where
+		F: Future<Output = ()> + Send + 'static,
+	{
+		self.cleanups.add(f);
 	}
 
 	/// Updates the progress
 	pub fn update(&self, progress: P) {
-		*self.progress.lock() = Some(progress);
+		// Note: We only publish the progress if we're still the current load,
+		//       and keep the task locked so a new load can't start meanwhile.
+		let task = self.state.task.lock();
+		if task.generation() == self.generation {
+			self.state.progress.store(Some(Arc::new(progress)));
+			drop(task);
+			self.state.changed();
+		}
 	}
 
 	/// Updates the progress
 	pub fn update_with<F>(&self, f: F)
 	where
 		F: FnOnce(&mut P),
-		P: Default,
+		P: Default + Clone,
 	{
-		// Note: This can't deadlock, as `AsyncLoadable::progress` only
-		//       tries to lock, and if it can't, it returns `None`.
-		f(self.progress.lock().get_or_insert_default());
+		// Note: We can't modify the progress in-place, since readers might
+		//       be holding onto it, so we modify a copy and publish it instead.
+		let task = self.state.task.lock();
+		if task.generation() == self.generation {
+			let mut progress = self.state.progress.load().as_deref().cloned().unwrap_or_default();
+			f(&mut progress);
+			self.state.progress.store(Some(Arc::new(progress)));
+			drop(task);
+			self.state.changed();
+		}
 	}
 }
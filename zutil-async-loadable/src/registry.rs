@@ -0,0 +1,244 @@
+//! Registry of live loadables
+
+// Imports
+use {
+	crate::{AsyncLoadable, Inner, LoadableId},
+	app_error::AppError,
+	parking_lot::Mutex,
+	std::{
+		any,
+		fmt,
+		ptr,
+		sync::{Arc, Weak},
+		time::Duration,
+	},
+};
+
+/// Registry
+static REGISTRY: Mutex<Vec<Entry>> = Mutex::new(vec![]);
+
+/// Registry entry
+struct Entry {
+	/// Name
+	name: String,
+
+	/// Loadable
+	node: Weak<dyn Inspect>,
+}
+
+/// Inspectable loadable
+trait Inspect: Send + Sync {
+	/// Returns information about this loadable
+	fn info(&self, name: &str) -> LoadableInfo;
+}
+
+impl<T, P> Inspect for Inner<T, P>
+where
+	T: Send + Sync + 'static,
+	P: fmt::Debug + Send + Sync + 'static,
+{
+	fn info(&self, name: &str) -> LoadableInfo {
+		let task = self.load.task.lock();
+		let is_loading = task.is_running();
+		let generation = task.generation();
+		let started_at = task.started_at();
+		let loaded_at = task.loaded_at();
+		drop(task);
+
+		let res = self.res.load();
+		let state = match &*res {
+			_ if is_loading => LoadableState::Loading,
+			Some(res) => match &**res {
+				Ok(_) => LoadableState::Loaded,
+				Err(err) => LoadableState::Failed(err.clone()),
+			},
+			None => LoadableState::Unloaded,
+		};
+
+		LoadableInfo {
+			name: name.to_owned(),
+			id: self.id,
+			type_name: any::type_name::<T>(),
+			state,
+			progress: self
+				.load
+				.progress
+				.load()
+				.as_deref()
+				.map(|progress| format!("{progress:?}")),
+			generation,
+			age: res.as_ref().and(loaded_at).map(|loaded_at| loaded_at.elapsed()),
+			loading_for: started_at.filter(|_| is_loading).map(|started_at| started_at.elapsed()),
+		}
+	}
+}
+
+impl<T, P> AsyncLoadable<T, P> {
+	/// Registers this loadable in the global registry under `name`.
+	///
+	/// The registry only keeps a weak reference to the loadable, so it's
+	/// removed from the registry once dropped.
+	///
+	/// If already registered, the loadable is renamed.
+	///
+	/// See [`RegistryDump`] for inspecting all registered loadables.
+	pub fn register<N>(&self, name: N)
+	where
+		N: Into<String>,
+		T: Send + Sync + 'static,
+		P: fmt::Debug + Send + Sync + 'static,
+	{
+		let node = Arc::downgrade(&self.inner) as Weak<dyn Inspect>;
+
+		let mut registry = REGISTRY.lock();
+		registry.retain(|entry| entry.node.strong_count() != 0 && !ptr::addr_eq(entry.node.as_ptr(), node.as_ptr()));
+		registry.push(Entry {
+			name: name.into(),
+			node,
+		});
+	}
+}
+
+/// Information about a loadable
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LoadableInfo {
+	/// Name
+	pub name: String,
+
+	/// Id
+	pub id: LoadableId,
+
+	/// Type name of the value
+	pub type_name: &'static str,
+
+	/// State
+	pub state: LoadableState,
+
+	/// Progress, formatted with it's `Debug` impl
+	pub progress: Option<String>,
+
+	/// Generation
+	pub generation: u64,
+
+	/// Time since the value was loaded, if loaded
+	pub age: Option<Duration>,
+
+	/// Time since the current load started, if loading
+	pub loading_for: Option<Duration>,
+}
+
+/// Loadable state
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum LoadableState {
+	/// Not loaded
+	Unloaded,
+
+	/// Loading
+	Loading,
+
+	/// Loaded successfully
+	Loaded,
+
+	/// Failed to load
+	Failed(AppError),
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for LoadableState {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		// Note: Like snapshots, errors are serialized as their (pretty-printed) messages.
+		match self {
+			Self::Unloaded => serializer.serialize_unit_variant("LoadableState", 0, "Unloaded"),
+			Self::Loading => serializer.serialize_unit_variant("LoadableState", 1, "Loading"),
+			Self::Loaded => serializer.serialize_unit_variant("LoadableState", 2, "Loaded"),
+			Self::Failed(err) =>
+				serializer.serialize_newtype_variant("LoadableState", 3, "Failed", &err.pretty().to_string()),
+		}
+	}
+}
+
+impl LoadableState {
+	/// Returns the name of this state
+	#[must_use]
+	pub const fn name(&self) -> &'static str {
+		match self {
+			Self::Unloaded => "unloaded",
+			Self::Loading => "loading",
+			Self::Loaded => "loaded",
+			Self::Failed(_) => "failed",
+		}
+	}
+}
+
+/// Dump of all registered loadables.
+///
+/// Can be rendered as text with it's `Display` impl, or as JSON with
+/// [`Self::to_json`] with the `json` feature. With the `serde` feature,
+/// it may also be serialized into any other format.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RegistryDump {
+	/// Loadables
+	pub loadables: Vec<LoadableInfo>,
+}
+
+impl RegistryDump {
+	/// Captures the current state of all registered loadables
+	#[must_use]
+	pub fn capture() -> Self {
+		// Note: We gather the information without the registry locked, since
+		//       it requires locking each loadable.
+		#[expect(clippy::needless_collect, reason = "We need to release the registry lock")]
+		let nodes = REGISTRY
+			.lock()
+			.iter()
+			.filter_map(|entry| entry.node.upgrade().map(|node| (entry.name.clone(), node)))
+			.collect::<Vec<_>>();
+		let loadables = nodes.into_iter().map(|(name, node)| node.info(&name)).collect();
+
+		Self { loadables }
+	}
+
+	/// Renders this dump as JSON
+	#[cfg(feature = "json")]
+	#[must_use]
+	#[expect(clippy::missing_panics_doc, reason = "All fields are always serializable")]
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(self).expect("Registry dumps should always be serializable")
+	}
+}
+
+impl fmt::Display for RegistryDump {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for info in &self.loadables {
+			write!(
+				f,
+				"{} {:?} ({}): {}, generation {}",
+				info.id,
+				info.name,
+				info.type_name,
+				info.state.name(),
+				info.generation
+			)?;
+			if let Some(progress) = &info.progress {
+				write!(f, ", progress {progress}")?;
+			}
+			if let Some(loading_for) = info.loading_for {
+				write!(f, ", loading for {loading_for:.2?}")?;
+			}
+			if let Some(age) = info.age {
+				write!(f, ", loaded {age:.2?} ago")?;
+			}
+			if let LoadableState::Failed(err) = &info.state {
+				write!(f, ", error: {}", err.pretty())?;
+			}
+			writeln!(f)?;
+		}
+
+		Ok(())
+	}
+}
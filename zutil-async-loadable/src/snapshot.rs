@@ -0,0 +1,54 @@
+//! Snapshots
+
+// Imports
+use {
+	crate::AsyncLoadable,
+	app_error::AppError,
+	serde::{Deserialize, Serialize},
+};
+
+/// A snapshot of the observable state of a loadable.
+///
+/// Errors are stored as their (pretty-printed) messages.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot<T, P = !> {
+	/// Result
+	pub res: Option<Result<T, String>>,
+
+	/// Whether the loadable was loading
+	pub is_loading: bool,
+
+	/// Progress
+	pub progress: Option<P>,
+}
+
+impl<T, P> AsyncLoadable<T, P> {
+	/// Takes a snapshot of this loadable
+	#[must_use]
+	pub fn snapshot(&self) -> Snapshot<T, P>
+	where
+		T: Clone,
+		P: Clone,
+	{
+		Snapshot {
+			res:        self
+				.get_arc()
+				.map(|res| res.as_ref().clone().map_err(|err| err.pretty().to_string())),
+			is_loading: self.is_loading(),
+			progress:   self.progress(),
+		}
+	}
+
+	/// Creates a loadable from a snapshot.
+	///
+	/// Since the loader can't be restored, loadables that were loading
+	/// are restored with their last result, if any, and without progress.
+	#[must_use]
+	pub fn from_snapshot(snapshot: Snapshot<T, P>) -> Self {
+		match snapshot.res {
+			Some(Ok(value)) => Self::from_value(value),
+			Some(Err(err)) => Self::from_res(Some(Err(AppError::fmt(err)))),
+			None => Self::new(),
+		}
+	}
+}
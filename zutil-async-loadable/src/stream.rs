@@ -0,0 +1,342 @@
+//! Streaming loadable
+
+// Imports
+use {
+	crate::{AsyncLoadable, LoadHandle, ProgressUpdater},
+	app_error::AppError,
+	parking_lot::Mutex,
+	std::{fmt, sync::Arc},
+	tokio::sync::Notify,
+};
+
+/// Stream items
+struct StreamItems<T> {
+	/// State
+	state: Mutex<StreamItemsState<T>>,
+
+	/// Notifies whenever items are added or the stream is done
+	notify: Notify,
+}
+
+/// Stream items state
+struct StreamItemsState<T> {
+	/// Items
+	items: Vec<T>,
+
+	/// Generation.
+	///
+	/// Incremented each time a new load starts, so that writers
+	/// from previous loads can't add any items.
+	generation: u64,
+
+	/// Whether the writer of the current generation is done
+	done: bool,
+
+	/// Whether the current load was stopped
+	stopped: bool,
+}
+
+/// An async fallible loadable stream of values.
+///
+/// Unlike [`AsyncLoadable`], the loader may publish partial items,
+/// which can be observed by readers immediately.
+pub struct AsyncStreamLoadable<T, P = !> {
+	/// Loadable
+	loadable: AsyncLoadable<(), P>,
+
+	/// Items
+	items: Arc<StreamItems<T>>,
+}
+
+impl<T, P> AsyncStreamLoadable<T, P> {
+	/// Creates a new, unloaded, stream
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			loadable: AsyncLoadable::new(),
+			items:    Arc::new(StreamItems {
+				state:  Mutex::new(StreamItemsState {
+					items:      vec![],
+					generation: 0,
+					done:       true,
+					stopped:    false,
+				}),
+				notify: Notify::new(),
+			}),
+		}
+	}
+
+	/// Clone this stream by reference-count.
+	///
+	/// The returned stream shares the same state as this one
+	#[must_use]
+	pub fn clone_rc(&self) -> Self {
+		Self {
+			loadable: self.loadable.clone_rc(),
+			items:    Arc::clone(&self.items),
+		}
+	}
+
+	/// Returns the inner loadable.
+	///
+	/// The loadable's value signals whether the stream completed
+	/// successfully.
+	#[must_use]
+	pub const fn loadable(&self) -> &AsyncLoadable<(), P> {
+		&self.loadable
+	}
+
+	/// Returns the status of this stream
+	#[must_use]
+	pub fn status(&self) -> StreamStatus {
+		match self.loadable.get() {
+			Some(Ok(())) => StreamStatus::Complete,
+			Some(Err(err)) => StreamStatus::Failed(err),
+			None if self.loadable.is_loading() => StreamStatus::Loading,
+			None if self.items.state.lock().stopped => StreamStatus::Stopped,
+			None => StreamStatus::Unloaded,
+		}
+	}
+
+	/// Returns all items currently in the stream
+	#[must_use]
+	pub fn items(&self) -> Vec<T>
+	where
+		T: Clone,
+	{
+		self.items.state.lock().items.clone()
+	}
+
+	/// Uses all items currently in the stream
+	pub fn with_items<F, O>(&self, f: F) -> O
+	where
+		F: FnOnce(&[T]) -> O,
+	{
+		f(&self.items.state.lock().items)
+	}
+
+	/// Returns the number of items currently in the stream
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.items.state.lock().items.len()
+	}
+
+	/// Returns if there are no items currently in the stream
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.items.state.lock().items.is_empty()
+	}
+
+	/// Waits for items after `start` to be available.
+	///
+	/// Returns all items after `start`, or an empty vector, if the
+	/// loader finished without adding any more items.
+	pub async fn next_items(&self, start: usize) -> Vec<T>
+	where
+		T: Clone,
+	{
+		#![expect(
+			clippy::future_not_send,
+			reason = "The future is `Send` when `T` and `P` are `Send + Sync`"
+		)]
+
+		loop {
+			// Note: See `AsyncLoadable::wait` for why we get the future before checking.
+			let wait_fut = self.items.notify.notified();
+
+			{
+				let state = self.items.state.lock();
+				if let Some(items) = state.items.get(start..) &&
+					!items.is_empty()
+				{
+					break items.to_vec();
+				}
+				if state.done {
+					break vec![];
+				}
+			}
+
+			wait_fut.await;
+		}
+	}
+
+	/// Waits for the stream to finish loading.
+	///
+	/// # Deadlocks
+	/// See [`AsyncLoadable::wait`].
+	pub async fn wait(&self) -> Result<(), AppError> {
+		#![expect(
+			clippy::future_not_send,
+			reason = "The future is `Send` when `T` and `P` are `Send + Sync`"
+		)]
+
+		self.loadable.wait().await
+	}
+
+	/// Gets the progress of the stream.
+	///
+	/// See [`AsyncLoadable::progress`] for details.
+	#[must_use]
+	pub fn progress(&self) -> Option<P>
+	where
+		P: Clone,
+	{
+		self.loadable.progress()
+	}
+
+	/// Returns if the stream is loading.
+	#[must_use]
+	pub fn is_loading(&self) -> bool {
+		self.loadable.is_loading()
+	}
+
+	/// Stops loading the stream.
+	///
+	/// Any items already added are kept, but the loader won't be able
+	/// to add any more, even if it isn't aborted immediately.
+	///
+	/// If not loading, does nothing
+	pub fn stop_loading(&self) {
+		if !self.loadable.is_loading() {
+			return;
+		}
+		self.loadable.stop_loading();
+
+		let mut state = self.items.state.lock();
+		state.generation += 1;
+		state.done = true;
+		state.stopped = true;
+		drop(state);
+
+		self.items.notify.notify_waiters();
+	}
+
+	/// Resets the stream, removing all items
+	pub fn reset(&self) {
+		let _: Option<_> = self.loadable.reset();
+
+		let mut state = self.items.state.lock();
+		state.items.clear();
+		state.generation += 1;
+		state.done = true;
+		state.stopped = false;
+		drop(state);
+
+		self.items.notify.notify_waiters();
+	}
+
+	/// Tries to load this stream and returns a handle to wait for it to finish.
+	///
+	/// Any items from a previous load are removed once loading starts.
+	///
+	/// If already loading, returns `None`.
+	pub fn try_load<F>(&self, f: F) -> Option<LoadHandle<()>>
+	where
+		F: AsyncFnOnce(StreamWriter<T>, ProgressUpdater<P>) -> Result<(), AppError>,
+		F::CallOnceFuture: Send + 'static,
+		T: Send + 'static,
+		P: Send + Sync + 'static,
+	{
+		// Note: This closure is only called when a new task will be spawned,
+		//       so it's the right place to start a new generation.
+		self.loadable.try_load(|progress| {
+			let mut state = self.items.state.lock();
+			state.items.clear();
+			state.generation += 1;
+			state.done = false;
+			state.stopped = false;
+			let writer = StreamWriter {
+				items:      Arc::clone(&self.items),
+				generation: state.generation,
+			};
+			drop(state);
+
+			f(writer, progress)
+		})
+	}
+}
+
+impl<T, P> Default for AsyncStreamLoadable<T, P> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: fmt::Debug, P: fmt::Debug> fmt::Debug for AsyncStreamLoadable<T, P> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut f = f.debug_struct("AsyncStreamLoadable");
+		f.field("loadable", &self.loadable);
+		match self.items.state.try_lock() {
+			Some(state) => f.field("items", &state.items).finish(),
+			None => f.finish_non_exhaustive(),
+		}
+	}
+}
+
+/// Stream status
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum StreamStatus {
+	/// Not loaded
+	Unloaded,
+
+	/// Loading
+	Loading,
+
+	/// Stopped before completing.
+	///
+	/// Any items added before stopping are kept.
+	Stopped,
+
+	/// Completed successfully
+	Complete,
+
+	/// Failed
+	Failed(AppError),
+}
+
+/// Stream writer.
+///
+/// Allows a loader to add items to a stream.
+///
+/// Once dropped, any readers waiting for items are woken up.
+pub struct StreamWriter<T> {
+	/// Items
+	items: Arc<StreamItems<T>>,
+
+	/// Generation
+	generation: u64,
+}
+
+impl<T> StreamWriter<T> {
+	/// Adds an item to the stream
+	pub fn push(&self, item: T) {
+		self.extend([item]);
+	}
+
+	/// Adds several items to the stream
+	pub fn extend<I>(&self, items: I)
+	where
+		I: IntoIterator<Item = T>,
+	{
+		let mut state = self.items.state.lock();
+		if state.generation != self.generation {
+			return;
+		}
+		state.items.extend(items);
+		drop(state);
+
+		self.items.notify.notify_waiters();
+	}
+}
+
+impl<T> Drop for StreamWriter<T> {
+	fn drop(&mut self) {
+		let mut state = self.items.state.lock();
+		if state.generation == self.generation {
+			state.done = true;
+		}
+		drop(state);
+
+		self.items.notify.notify_waiters();
+	}
+}
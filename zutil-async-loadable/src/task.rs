@@ -0,0 +1,127 @@
+//! Loader task
+
+// Imports
+use {
+	crate::callback::{Callbacks, OnChange},
+	arc_swap::ArcSwapOption,
+	parking_lot::Mutex,
+	std::time::Instant,
+	tokio::task,
+};
+
+/// Load state
+pub struct LoadState<P> {
+	/// Task
+	pub task: Mutex<Task>,
+
+	/// Progress
+	pub progress: ArcSwapOption<P>,
+
+	/// Change callbacks
+	pub on_change: Callbacks<OnChange>,
+}
+
+impl<P> LoadState<P> {
+	/// Creates a new load state
+	pub fn new() -> Self {
+		Self {
+			task:      Mutex::new(Task::default()),
+			progress:  ArcSwapOption::empty(),
+			on_change: Callbacks::new(),
+		}
+	}
+
+	/// Calls all change callbacks
+	pub fn changed(&self) {
+		self.on_change.call_all(|f| f());
+	}
+}
+
+/// Loader task
+#[derive(Default, Debug)]
+pub struct Task {
+	/// Handle
+	handle: Option<task::AbortHandle>,
+
+	/// Whether a task is running.
+	///
+	/// Set as soon as a task is started, even before it's handle is set.
+	running: bool,
+
+	/// Generation.
+	///
+	/// Incremented whenever a task is started or stopped. Only
+	/// the task with the current generation may write the result
+	/// and progress.
+	generation: u64,
+
+	/// Instant the current task was started
+	started_at: Option<Instant>,
+
+	/// Instant the value was last loaded
+	loaded_at: Option<Instant>,
+}
+
+impl Task {
+	/// Returns if the task is running
+	pub fn is_running(&self) -> bool {
+		self.running && self.handle.as_ref().is_none_or(|handle| !handle.is_finished())
+	}
+
+	/// Returns the current generation
+	pub const fn generation(&self) -> u64 {
+		self.generation
+	}
+
+	/// Returns the instant the current task was started
+	pub const fn started_at(&self) -> Option<Instant> {
+		self.started_at
+	}
+
+	/// Returns the instant the value was last loaded
+	pub const fn loaded_at(&self) -> Option<Instant> {
+		self.loaded_at
+	}
+
+	/// Records that the value was just loaded.
+	///
+	/// The current task, if any, is considered finished from now on, even
+	/// if it hasn't returned yet.
+	pub fn set_loaded(&mut self) {
+		self.set_finished();
+		self.loaded_at = Some(Instant::now());
+	}
+
+	/// Records that the current task finished, without loading a value.
+	pub fn set_finished(&mut self) {
+		self.handle = None;
+		self.running = false;
+	}
+
+	/// Stops the current task, if any.
+	///
+	/// Even if the task isn't aborted immediately, it won't be able to
+	/// write the result or progress.
+	pub fn stop(&mut self) {
+		if let Some(handle) = self.handle.take() {
+			handle.abort();
+		}
+		self.running = false;
+		self.generation += 1;
+	}
+
+	/// Starts a new generation, stopping the current task, if any.
+	///
+	/// Returns the new generation.
+	pub fn start(&mut self) -> u64 {
+		self.stop();
+		self.running = true;
+		self.started_at = Some(Instant::now());
+		self.generation
+	}
+
+	/// Sets the handle of the task of the current generation
+	pub fn set_handle(&mut self, handle: task::AbortHandle) {
+		self.handle = Some(handle);
+	}
+}
@@ -0,0 +1,126 @@
+//! `egui` integration
+
+// Imports
+use {
+	crate::{AsyncLoadable, CallbackHandle, LoadableId, dependency::DependencyNode},
+	parking_lot::Mutex,
+	std::sync::{Arc, Weak},
+};
+
+/// Progress that can be displayed as an `egui` progress bar
+pub trait EguiProgress {
+	/// Returns the progress bar for this progress
+	fn progress_bar(&self) -> egui::ProgressBar;
+}
+
+impl EguiProgress for ! {
+	fn progress_bar(&self) -> egui::ProgressBar {
+		*self
+	}
+}
+
+impl EguiProgress for f32 {
+	fn progress_bar(&self) -> egui::ProgressBar {
+		egui::ProgressBar::new(*self).show_percentage()
+	}
+}
+
+impl<T, P> AsyncLoadable<T, P> {
+	/// Requests a repaint of `ctx` whenever this loadable changes.
+	///
+	/// See [`AsyncLoadable::on_change`] for what counts as a change.
+	pub fn repaint_on_change(&self, ctx: &egui::Context) -> CallbackHandle
+	where
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let ctx = ctx.clone();
+		self.on_change(move || ctx.request_repaint())
+	}
+
+	/// Shows this loadable.
+	///
+	/// While loading, shows a spinner, along with a progress bar, if any
+	/// progress is available. If loading failed, shows the error. Otherwise,
+	/// if loaded, shows the contents with `add_contents` and returns its result.
+	///
+	/// The first time a loadable is shown on a context, a repaint is requested
+	/// whenever it changes (see [`Self::repaint_on_change`]). The hook is kept
+	/// in the context's temporary data until the data is cleared, or until the
+	/// next loadable is shown after this one is dropped. While registered, the
+	/// hook keeps the context alive.
+	pub fn show<R>(&self, ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui, &T) -> R) -> Option<R>
+	where
+		T: Send + Sync + 'static,
+		P: EguiProgress + Send + Sync + 'static,
+	{
+		self.register_repaint(ui.ctx());
+
+		if let Some(res) = self.get_arc() {
+			return match &*res {
+				Ok(value) => Some(add_contents(ui, value)),
+				Err(err) => {
+					ui.colored_label(ui.visuals().error_fg_color, format!("Error: {}", err.pretty()));
+					None
+				},
+			};
+		}
+
+		if self.is_loading() {
+			ui.horizontal(|ui| {
+				ui.spinner();
+				if let Some(progress) = self.inner.load.progress.load().as_deref() {
+					ui.add(progress.progress_bar());
+				}
+			});
+		}
+
+		None
+	}
+
+	/// Registers the repaint hook on `ctx`, if not already registered
+	fn register_repaint(&self, ctx: &egui::Context)
+	where
+		T: Send + Sync + 'static,
+		P: Send + Sync + 'static,
+	{
+		let hooks = ctx.data_mut(|data| {
+			data.get_temp_mut_or_default::<RepaintHooks>(egui::Id::new("zutil-async-loadable-repaint"))
+				.clone()
+		});
+		let mut hooks = hooks.0.lock();
+
+		// Note: We remove the hooks of any dropped loadables here, since we
+		//       have no way of being notified when they're dropped.
+		hooks.retain(|hook| hook.loadable.strong_count() != 0);
+		if hooks.iter().any(|hook| hook.id == self.id()) {
+			return;
+		}
+
+		let loadable = Arc::downgrade(&self.inner);
+		hooks.push(RepaintHook {
+			id: self.id(),
+			loadable,
+			_handle: self.repaint_on_change(ctx),
+		});
+	}
+}
+
+/// Repaint hooks registered on a context.
+///
+/// Stored in the context's temporary data, so that the hooks are
+/// unregistered once the data is cleared.
+#[derive(Clone, Default)]
+struct RepaintHooks(Arc<Mutex<Vec<RepaintHook>>>);
+
+/// Repaint hook
+struct RepaintHook {
+	/// Loadable id
+	id: LoadableId,
+
+	/// Loadable
+	loadable: Weak<dyn DependencyNode>,
+
+	/// Callback handle
+	_handle: CallbackHandle,
+}
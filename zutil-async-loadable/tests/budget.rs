@@ -0,0 +1,87 @@
+//! Budget tests
+
+// Imports
+use {
+	std::sync::{
+		Arc,
+		atomic::{self, AtomicU32},
+	},
+	zutil_async_loadable::{AsyncLazy, AsyncLoadable, MemoryBudget},
+};
+
+
+#[tokio::test]
+async fn evict_least_recently_accessed() {
+	let budget = MemoryBudget::new(10);
+	let lazy1 = AsyncLazy::<u32>::new(async |_| Ok(1));
+	let lazy2 = AsyncLazy::<u32>::new(async |_| Ok(2));
+	let lazy3 = AsyncLazy::<u32>::new(async |_| Ok(3));
+	for lazy in [&lazy1, &lazy2, &lazy3] {
+		budget.add(lazy, |_| 4);
+	}
+
+	assert_eq!(lazy1.get_or_load().await, Ok(1));
+	assert_eq!(lazy2.get_or_load().await, Ok(2));
+	assert_eq!(budget.used(), 8);
+
+	// Access the first, so the second is evicted
+	assert_eq!(lazy1.get(), Some(Ok(1)));
+	assert_eq!(lazy3.get_or_load().await, Ok(3));
+	assert_eq!(budget.used(), 8);
+	assert_eq!(lazy1.get(), Some(Ok(1)));
+	assert_eq!(lazy2.get(), None);
+	assert_eq!(lazy3.get(), Some(Ok(3)));
+}
+
+#[tokio::test]
+async fn reload_evicted() {
+	/// Number of times we've been loaded
+	static LOADS: AtomicU32 = AtomicU32::new(0);
+
+	let budget = MemoryBudget::new(10);
+	let lazy = AsyncLazy::<u32>::new(|_| async { Ok(LOADS.fetch_add(1, atomic::Ordering::Relaxed)) });
+	budget.add(&lazy, |_| 5);
+
+	assert_eq!(lazy.get_or_load().await, Ok(0));
+	budget.set_limit(0);
+	assert_eq!(lazy.get(), None);
+	assert_eq!(budget.used(), 0);
+
+	budget.set_limit(10);
+	assert_eq!(lazy.get_or_load().await, Ok(1));
+	assert_eq!(budget.used(), 5);
+}
+
+#[tokio::test]
+async fn remove() {
+	let budget = MemoryBudget::new(10);
+	let lazy = AsyncLazy::<u32>::new(async |_| Ok(1));
+	budget.add(&lazy, |_| 5);
+
+	assert_eq!(lazy.get_or_load().await, Ok(1));
+	assert_eq!(budget.used(), 5);
+
+	budget.remove(&lazy);
+	assert_eq!(budget.used(), 0);
+	budget.set_limit(0);
+	assert_eq!(lazy.get(), Some(Ok(1)));
+}
+
+#[tokio::test]
+async fn weigh_with_budget() {
+	let budget = Arc::new(MemoryBudget::new(10));
+	let lazy = AsyncLazy::<u32>::new(async |_| Ok(1));
+	let other = AsyncLoadable::<u32>::new();
+	budget.add(&lazy, {
+		let budget = Arc::downgrade(&budget);
+		move |_| {
+			if let Some(budget) = budget.upgrade() {
+				budget.remove(&other);
+			}
+			5
+		}
+	});
+
+	assert_eq!(lazy.get_or_load().await, Ok(1));
+	assert_eq!(budget.used(), 5);
+}
@@ -0,0 +1,72 @@
+//! Callback tests
+
+// Imports
+use {
+	parking_lot::Mutex,
+	std::sync::{
+		Arc,
+		atomic::{self, AtomicUsize},
+	},
+	zutil_async_loadable::AsyncLoadable,
+};
+
+
+#[tokio::test]
+async fn on_loaded() {
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let loaded = Arc::new(Mutex::new(vec![]));
+	let handle = loadable.on_loaded({
+		let loaded = Arc::clone(&loaded);
+		move |res| loaded.lock().push(res.clone())
+	});
+
+	assert_eq!(loadable.try_load_or_wait(async |_| Ok(1)).await, Ok(1));
+	assert_eq!(*loaded.lock(), [Ok(1)]);
+
+	handle.unregister();
+	assert_eq!(loadable.force_reload(async |_| Ok(2)).await, Ok(2));
+	assert_eq!(*loaded.lock(), [Ok(1)]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn on_loaded_not_loading() {
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let was_loading = Arc::new(Mutex::new(None));
+	let _handle = loadable.on_loaded({
+		let loadable = loadable.clone_rc();
+		let was_loading = Arc::clone(&was_loading);
+		move |_| *was_loading.lock() = Some(loadable.is_loading())
+	});
+
+	assert_eq!(loadable.try_load_or_wait(async |_| Ok(1)).await, Ok(1));
+	assert_eq!(*was_loading.lock(), Some(false));
+}
+
+#[tokio::test]
+async fn on_change() {
+	let loadable = AsyncLoadable::<(), u32>::new();
+
+	let changes = Arc::new(AtomicUsize::new(0));
+	loadable
+		.on_change({
+			let changes = Arc::clone(&changes);
+			move || _ = changes.fetch_add(1, atomic::Ordering::Relaxed)
+		})
+		.detach();
+
+	// Note: 2 progress updates and finishing loading
+	loadable
+		.try_load_or_wait(async |progress| {
+			progress.update(1);
+			progress.update(2);
+			Ok(())
+		})
+		.await
+		.expect("Should be successful");
+	assert_eq!(changes.load(atomic::Ordering::Relaxed), 3);
+
+	let _: Option<_> = loadable.reset();
+	assert_eq!(changes.load(atomic::Ordering::Relaxed), 4);
+}
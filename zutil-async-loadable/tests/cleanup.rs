@@ -0,0 +1,103 @@
+//! Cleanup tests
+
+// Imports
+use {
+	app_error::AppError,
+	std::sync::Arc,
+	tokio::sync::{Mutex, mpsc},
+	zutil_async_loadable::AsyncLoadable,
+};
+
+
+#[tokio::test]
+async fn cleanup_on_abort() {
+	let loadable = AsyncLoadable::<u32>::new();
+	let (cleanup_tx, mut cleanup_rx) = mpsc::unbounded_channel();
+
+	let lock = Arc::new(Mutex::new(()));
+	let lock_guard = lock.lock().await;
+
+	let (started_tx, mut started_rx) = mpsc::unbounded_channel();
+	let load_handle = loadable
+		.try_load({
+			let lock = Arc::clone(&lock);
+			async move |progress| {
+				progress.on_cleanup(async move {
+					cleanup_tx.send(()).expect("Should be able to send");
+				});
+				started_tx.send(()).expect("Should be able to send");
+				let _ = lock.lock().await;
+				Ok(1)
+			}
+		})
+		.expect("Should not be loading");
+	started_rx.recv().await.expect("Should have started");
+
+	loadable.stop_loading();
+	load_handle.await.expect_err("Should have been stopped");
+	cleanup_rx.recv().await.expect("Should have cleaned up");
+	drop(lock_guard);
+}
+
+#[tokio::test]
+async fn cleanup_on_failure() {
+	let loadable = AsyncLoadable::<u32>::new();
+	let (cleanup_tx, mut cleanup_rx) = mpsc::unbounded_channel();
+
+	loadable
+		.try_load(async move |progress| {
+			progress.on_cleanup(async move {
+				cleanup_tx.send(1).expect("Should be able to send");
+			});
+			Err(AppError::msg("Error"))
+		})
+		.expect("Should not be loading")
+		.await
+		.expect_err("Should be an error");
+
+	assert_eq!(cleanup_rx.recv().await, Some(1));
+}
+
+#[tokio::test]
+async fn no_cleanup_on_success() {
+	let loadable = AsyncLoadable::<u32>::new();
+	let (cleanup_tx, mut cleanup_rx) = mpsc::unbounded_channel::<()>();
+
+	loadable
+		.try_load(async move |progress| {
+			progress.on_cleanup(async move {
+				cleanup_tx.send(()).expect("Should be able to send");
+			});
+			Ok(1)
+		})
+		.expect("Should not be loading")
+		.await
+		.expect("Should be successful");
+
+	// Note: The cleanup action was dropped, so the channel closes without a message.
+	assert_eq!(cleanup_rx.recv().await, None);
+}
+
+#[test]
+fn cleanup_without_timers() {
+	let runtime = tokio::runtime::Builder::new_current_thread()
+		.build()
+		.expect("Unable to build runtime");
+	let (cleanup_tx, mut cleanup_rx) = mpsc::unbounded_channel();
+
+	runtime.block_on(async move {
+		let loadable = AsyncLoadable::<u32>::new();
+		loadable
+			.try_load(async move |progress| {
+				progress.on_cleanup(async move {
+					cleanup_tx.send(1).expect("Should be able to send");
+				});
+				Err(AppError::msg("Error"))
+			})
+			.expect("Should not be loading")
+			.await
+			.expect_err("Should be an error");
+
+		assert_eq!(cleanup_rx.recv().await, Some(1));
+	});
+}
@@ -0,0 +1,122 @@
+//! Dependency tests
+
+// Imports
+use zutil_async_loadable::AsyncLoadable;
+
+
+#[tokio::test]
+async fn reset_cascades() {
+	let config = AsyncLoadable::<u32>::from_value(1);
+	let derived = AsyncLoadable::<u32>::from_value(2);
+	let derived2 = AsyncLoadable::<u32>::from_value(3);
+	derived.add_dependency(&config).expect("Should not be a cycle");
+	derived2.add_dependency(&derived).expect("Should not be a cycle");
+
+	let _: Option<_> = config.reset();
+	assert_eq!(config.get(), None);
+	assert_eq!(derived.get(), None);
+	assert_eq!(derived2.get(), None);
+}
+
+#[tokio::test]
+async fn reset_reloads() {
+	let config = AsyncLoadable::<u32>::from_value(1);
+	let derived = AsyncLoadable::<u32>::from_value(0);
+	derived.add_dependency(&config).expect("Should not be a cycle");
+	derived.set_reload_on_invalidate({
+		let config = config.clone_rc();
+		move |_| {
+			let config = config.clone_rc();
+			async move { Ok(config.try_load_or_wait(async |_| Ok(5)).await? * 2) }
+		}
+	});
+
+	let _: Option<_> = config.reset();
+	assert_eq!(derived.wait().await, Ok(10));
+	assert_eq!(config.get(), Some(Ok(5)));
+}
+
+#[tokio::test]
+async fn reload_cascades() {
+	let config = AsyncLoadable::<u32>::from_value(1);
+	let derived = AsyncLoadable::<u32>::from_value(0);
+	let derived2 = AsyncLoadable::<u32>::from_value(3);
+	derived.add_dependency(&config).expect("Should not be a cycle");
+	derived2.add_dependency(&derived).expect("Should not be a cycle");
+	derived.set_reload_on_invalidate({
+		let config = config.clone_rc();
+		move |_| {
+			let config = config.clone_rc();
+			async move { Ok(config.try_load_or_wait(async |_| Ok(5)).await? * 2) }
+		}
+	});
+
+	let _: Option<_> = config.reset();
+	assert_eq!(derived2.get(), Some(Ok(3)));
+	assert_eq!(derived.wait().await, Ok(10));
+	assert_eq!(derived2.get(), None);
+}
+
+#[tokio::test]
+async fn cycles() {
+	let a = AsyncLoadable::<()>::new();
+	let b = AsyncLoadable::<()>::new();
+	let c = AsyncLoadable::<()>::new();
+	b.add_dependency(&a).expect("Should not be a cycle");
+	c.add_dependency(&b).expect("Should not be a cycle");
+
+	a.add_dependency(&a).expect_err("Should be a cycle");
+	a.add_dependency(&c).expect_err("Should be a cycle");
+	c.add_dependency(&a).expect("Should not be a cycle");
+}
+
+#[tokio::test]
+async fn graph() {
+	let a = AsyncLoadable::<()>::new();
+	let b = AsyncLoadable::<u32>::new();
+	b.add_dependency(&a).expect("Should not be a cycle");
+
+	assert_eq!(b.dependencies(), [a.id()]);
+	assert_eq!(a.dependents(), [b.id()]);
+
+	let graph = a.dependency_graph();
+	assert_eq!(graph.nodes.len(), 2);
+	assert_eq!(graph.edges, [(a.id(), b.id())]);
+
+	assert!(b.remove_dependency(&a));
+	assert!(a.dependents().is_empty());
+}
+
+#[tokio::test]
+async fn remove_dead() {
+	let a = AsyncLoadable::<()>::new();
+	let b = AsyncLoadable::<()>::new();
+	let c = AsyncLoadable::<()>::new();
+	a.add_dependency(&b).expect("Should not be a cycle");
+	drop(b);
+
+	assert!(!a.remove_dependency(&c));
+	assert!(a.dependencies().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn concurrent_cycles() {
+	for _ in 0..100 {
+		let a = AsyncLoadable::<()>::new();
+		let b = AsyncLoadable::<()>::new();
+
+		let add_ab = tokio::task::spawn_blocking({
+			let a = a.clone_rc();
+			let b = b.clone_rc();
+			move || a.add_dependency(&b)
+		});
+		let add_ba = tokio::task::spawn_blocking({
+			let a = a.clone_rc();
+			let b = b.clone_rc();
+			move || b.add_dependency(&a)
+		});
+		let add_ab = add_ab.await.expect("Unable to join task");
+		let add_ba = add_ba.await.expect("Unable to join task");
+		assert!(add_ab.is_ok() != add_ba.is_ok(), "{add_ab:?} {add_ba:?}");
+	}
+}
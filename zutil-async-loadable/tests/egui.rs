@@ -0,0 +1,73 @@
+//! egui tests
+
+// Imports
+use {
+	std::{io, sync::Arc},
+	tokio::sync::Mutex,
+	zutil_async_loadable::AsyncLoadable,
+};
+
+
+#[tokio::test]
+async fn show() {
+	let ctx = egui::Context::default();
+	let loadable = AsyncLoadable::<u32, f32>::new();
+
+	let lock = Arc::new(Mutex::new(()));
+	let lock_guard = lock.lock().await;
+
+	let load_handle = loadable
+		.try_load({
+			let lock = Arc::clone(&lock);
+			async move |progress| {
+				progress.update(0.5);
+				let _ = lock.lock().await;
+				Ok(5)
+			}
+		})
+		.expect("Should not be loading");
+
+	let mut shown = None;
+	let _ = ctx.run_ui(egui::RawInput::default(), |ui| {
+		shown = loadable.show(ui, |_, &value| value);
+	});
+	assert_eq!(shown, None);
+
+	drop(lock_guard);
+	load_handle.await.expect("Should be successful");
+
+	let _ = ctx.run_ui(egui::RawInput::default(), |ui| {
+		shown = loadable.show(ui, |_, &value| value);
+	});
+	assert_eq!(shown, Some(5));
+}
+
+#[tokio::test]
+async fn show_error() {
+	let ctx = egui::Context::default();
+	let loadable = AsyncLoadable::<u32>::from_error(&io::Error::other("Error"));
+
+	let mut shown = None;
+	let _ = ctx.run_ui(egui::RawInput::default(), |ui| {
+		shown = loadable.show(ui, |_, &value| value);
+	});
+	assert_eq!(shown, None);
+}
+
+#[tokio::test]
+async fn repaint_on_change() {
+	let ctx = egui::Context::default();
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let _handle = loadable.repaint_on_change(&ctx);
+	let _ = ctx.run_ui(egui::RawInput::default(), |_| ());
+	let _ = ctx.run_ui(egui::RawInput::default(), |_| ());
+	assert!(!ctx.has_requested_repaint());
+
+	loadable
+		.try_load(async |_| Ok(5))
+		.expect("Should not be loading")
+		.await
+		.expect("Should be successful");
+	assert!(ctx.has_requested_repaint());
+}
@@ -0,0 +1,70 @@
+//! Fallback tests
+
+// Imports
+use {
+	app_error::AppError,
+	zutil_async_loadable::{AsyncLoadable, Fallback, WithFallback},
+};
+
+
+#[tokio::test]
+async fn fallback_value() {
+	let loadable = AsyncLoadable::<u32>::new();
+	loadable.set_fallback(Fallback::Value(0));
+
+	let err = AppError::msg("Error");
+	loadable
+		.try_load({
+			let err = err.clone();
+			async move |_| Err(err)
+		})
+		.expect("Should not be loading")
+		.await
+		.expect_err("Should be an error");
+
+	assert_eq!(loadable.get(), Some(Err(err.clone())));
+	assert_eq!(
+		loadable.get_with_fallback(),
+		Some(WithFallback {
+			res:      Err(err),
+			fallback: Some(0),
+		})
+	);
+	assert_eq!(loadable.get_or_fallback(), Some(Ok(0)));
+	assert_eq!(loadable.wait_or_fallback().await, Ok(0));
+}
+
+#[tokio::test]
+async fn last_good() {
+	let loadable = AsyncLoadable::<u32>::new();
+	loadable.set_fallback(Fallback::LastGood);
+
+	assert_eq!(loadable.try_load_or_wait(async |_| Ok(1)).await, Ok(1));
+	assert_eq!(
+		loadable.get_with_fallback(),
+		Some(WithFallback {
+			res:      Ok(1),
+			fallback: None,
+		})
+	);
+
+	let err = AppError::msg("Error");
+	loadable
+		.force_reload({
+			let err = err.clone();
+			async move |_| Err(err)
+		})
+		.await
+		.expect_err("Should be an error");
+	assert_eq!(
+		loadable.get_with_fallback(),
+		Some(WithFallback {
+			res:      Err(err),
+			fallback: Some(1),
+		})
+	);
+	assert_eq!(loadable.wait_or_fallback().await, Ok(1));
+
+	let _: Option<_> = loadable.reset();
+	assert_eq!(loadable.fallback(), None);
+}
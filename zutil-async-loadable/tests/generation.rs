@@ -0,0 +1,102 @@
+//! Generation tests
+
+// Imports
+use {
+	std::sync::{Arc, mpsc},
+	tokio::sync::Mutex,
+	zutil_async_loadable::{AsyncLoadable, ProgressUpdater},
+};
+
+
+#[tokio::test]
+async fn force_reload() {
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let lock = Arc::new(Mutex::new(()));
+	let lock_guard = lock.lock().await;
+
+	let load_handle = loadable
+		.try_load({
+			let lock = Arc::clone(&lock);
+			async move |_| {
+				let _ = lock.lock().await;
+				Ok(1)
+			}
+		})
+		.expect("Should not be loading");
+	let generation = loadable.generation();
+
+	assert_eq!(loadable.force_reload(async |_| Ok(2)).await, Ok(2));
+	assert!(loadable.generation() > generation);
+
+	drop(lock_guard);
+	load_handle.await.expect_err("Should have been stopped");
+	assert_eq!(loadable.get(), Some(Ok(2)));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn no_stale_writes() {
+	let loadable = AsyncLoadable::<u32, u32>::new();
+
+	// Note: The old loader blocks, so aborting it can't land until it finishes.
+	let (started_tx, started_rx) = mpsc::channel();
+	let (resume_tx, resume_rx) = mpsc::channel::<()>();
+	let load_handle = loadable
+		.try_load(async move |progress| {
+			started_tx.send(()).expect("Should be able to send");
+			resume_rx.recv().expect("Should be able to receive");
+			progress.update(100);
+			Ok(1)
+		})
+		.expect("Should not be loading");
+	tokio::task::spawn_blocking(move || started_rx.recv())
+		.await
+		.expect("Should not panic")
+		.expect("Should be able to receive");
+
+	assert_eq!(loadable.force_reload(async |_| Ok(2)).await, Ok(2));
+
+	resume_tx.send(()).expect("Should be able to send");
+	load_handle.await.expect_err("Should have been stopped");
+	assert_eq!(loadable.get(), Some(Ok(2)));
+	assert_eq!(loadable.progress(), None);
+}
+
+#[tokio::test]
+async fn stop_loading() {
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let lock = Arc::new(Mutex::new(()));
+	let lock_guard = lock.lock().await;
+
+	let load_handle = loadable
+		.try_load({
+			let lock = Arc::clone(&lock);
+			async move |_| {
+				let _ = lock.lock().await;
+				Ok(1)
+			}
+		})
+		.expect("Should not be loading");
+
+	loadable.stop_loading();
+	assert!(!loadable.is_loading());
+	drop(lock_guard);
+
+	load_handle.await.expect_err("Should have been stopped");
+	assert_eq!(loadable.get(), None);
+	assert_eq!(loadable.try_load_or_wait(async |_| Ok(2)).await, Ok(2));
+}
+
+#[tokio::test]
+async fn sync_progress() {
+	let loadable = AsyncLoadable::<u32, u32>::new();
+
+	let load_handle = loadable
+		.try_load(|progress: ProgressUpdater<u32>| {
+			progress.update(1);
+			async { Ok(5) }
+		})
+		.expect("Should not be loading");
+	assert_eq!(load_handle.await, Ok(5));
+}
@@ -0,0 +1,118 @@
+//! Group tests
+
+// Imports
+use {
+	std::{sync::Arc, time::Duration},
+	tokio::sync::Mutex,
+	zutil_async_loadable::{AsyncLoadable, GroupProgress, LoadGroup},
+};
+
+
+#[tokio::test]
+async fn wait() {
+	let group = LoadGroup::new();
+	let loadable1 = AsyncLoadable::<u32>::new();
+	let loadable2 = AsyncLoadable::<u32>::new();
+
+	let lock = Arc::new(Mutex::new(()));
+	let lock_guard = lock.lock().await;
+
+	let _: Option<_> = group.try_load(&loadable1, async |_| Ok(1));
+	let _: Option<_> = group.try_load(&loadable2, {
+		let lock = Arc::clone(&lock);
+		async move |_| {
+			let _ = lock.lock().await;
+			Ok(2)
+		}
+	});
+	assert_eq!(group.len(), 2);
+
+	let mut progresses = vec![];
+	let wait = group.wait_with_progress(|progress| progresses.push(progress));
+	drop(lock_guard);
+	wait.await.expect("Should be successful");
+
+	assert_eq!(
+		progresses.last(),
+		Some(&GroupProgress {
+			total:   2,
+			loading: 0,
+			loaded:  2,
+			failed:  0,
+		})
+	);
+	assert_eq!(loadable1.get(), Some(Ok(1)));
+	assert_eq!(loadable2.get(), Some(Ok(2)));
+}
+
+#[tokio::test]
+async fn cancel() {
+	let group = LoadGroup::new();
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let load_handle = group
+		.try_load(&loadable, async |_| std::future::pending().await)
+		.expect("Should not be loading");
+	assert_eq!(group.progress().loading, 1);
+
+	group.cancel();
+	assert!(!loadable.is_loading());
+	load_handle.await.expect_err("Should have been cancelled");
+	group.wait().await.expect_err("Should not be loaded");
+}
+
+#[tokio::test]
+async fn cancel_on_drop() {
+	let loadable = AsyncLoadable::<u32>::new();
+
+	{
+		let group = LoadGroup::new();
+		let _: Option<_> = group.try_load(&loadable, async |_| std::future::pending().await);
+		assert!(loadable.is_loading());
+	}
+
+	assert!(!loadable.is_loading());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn wait_multi_thread() {
+	for _ in 0..100 {
+		let group = LoadGroup::new();
+		let loadable = AsyncLoadable::<u32>::new();
+
+		let _: Option<_> = group.try_load(&loadable, async |_| Ok(1));
+		tokio::time::timeout(Duration::from_secs(5), group.wait())
+			.await
+			.expect("Should not hang")
+			.expect("Should be successful");
+	}
+}
+
+#[tokio::test]
+async fn wait_panic() {
+	let group = LoadGroup::new();
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let _: Option<_> = group.try_load(&loadable, async |_| panic!("Oh no"));
+	tokio::time::timeout(Duration::from_secs(5), group.wait())
+		.await
+		.expect("Should not hang")
+		.expect_err("Should not be loaded");
+	assert!(!loadable.is_loading());
+}
+
+#[tokio::test]
+async fn wait_aborted() {
+	let group = LoadGroup::new();
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let load_handle = group
+		.try_load(&loadable, async |_| std::future::pending().await)
+		.expect("Should not be loading");
+	drop(load_handle.into_future());
+	tokio::time::timeout(Duration::from_secs(5), group.wait())
+		.await
+		.expect("Should not hang")
+		.expect_err("Should not be loaded");
+	assert!(!loadable.is_loading());
+}
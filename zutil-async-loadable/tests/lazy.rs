@@ -0,0 +1,34 @@
+//! Lazy tests
+
+// Imports
+use {
+	std::sync::{
+		LazyLock,
+		atomic::{self, AtomicU32},
+	},
+	zutil_async_loadable::AsyncLazy,
+};
+
+
+#[tokio::test]
+async fn get_or_load() {
+	/// Lazy value
+	static LAZY: LazyLock<AsyncLazy<u32>> = LazyLock::new(|| AsyncLazy::new(async |_| Ok(5)));
+
+	assert_eq!(LAZY.get(), None);
+	assert_eq!(LAZY.get_or_load().await, Ok(5));
+	assert_eq!(LAZY.get(), Some(Ok(5)));
+}
+
+#[tokio::test]
+async fn reload() {
+	/// Number of times we've been loaded
+	static LOADS: AtomicU32 = AtomicU32::new(0);
+
+	let lazy = AsyncLazy::<u32>::new(|_| async { Ok(LOADS.fetch_add(1, atomic::Ordering::Relaxed)) });
+
+	assert_eq!(lazy.get_or_load().await, Ok(0));
+	assert_eq!(lazy.get_or_load().await, Ok(0));
+	assert_eq!(lazy.reload().await, Ok(1));
+	assert_eq!(lazy.get(), Some(Ok(1)));
+}
@@ -65,3 +65,52 @@ async fn load_loading() {
 	assert_eq!(loadable.get(), Some(Ok(())));
 	assert!(!loadable.is_loading());
 }
+
+#[tokio::test]
+async fn load_progress() {
+	let loadable = AsyncLoadable::<(), u32>::new();
+
+	let lock = Arc::new(Mutex::new(()));
+	let lock_guard = lock.lock().await;
+
+	let (progress_tx, progress_rx) = tokio::sync::oneshot::channel();
+	let load_handle = loadable
+		.try_load({
+			let lock = Arc::clone(&lock);
+			async move |progress| {
+				progress.update(1);
+				progress.update_with(|progress| *progress += 1);
+				_ = progress_tx.send(());
+				let _ = lock.lock().await;
+				Ok(())
+			}
+		})
+		.expect("Should not be loading");
+
+	progress_rx.await.expect("Loader should send");
+	assert_eq!(loadable.progress(), Some(2));
+
+	drop(lock_guard);
+	assert_eq!(load_handle.await, Ok(()));
+	assert_eq!(loadable.progress(), None);
+}
+
+#[tokio::test]
+async fn reset() {
+	let loadable = AsyncLoadable::<u32>::from_value(1);
+	assert_eq!(loadable.reset(), Some(Ok(1)));
+	assert_eq!(loadable.reset(), None);
+}
+
+#[tokio::test]
+async fn reset_arc() {
+	/// Value that can't be cloned
+	#[derive(PartialEq, Debug)]
+	struct NoClone;
+
+	let loadable = AsyncLoadable::<NoClone>::from_value(NoClone);
+	let value = loadable.get_arc().expect("Should be loaded");
+	let res = loadable.reset_arc().expect("Should be loaded");
+	assert!(Arc::ptr_eq(&value, &res));
+	assert_eq!(loadable.get_arc(), None);
+}
@@ -0,0 +1,114 @@
+//! Local tests
+
+// Imports
+use {
+	std::rc::Rc,
+	tokio::{sync::Mutex, task::LocalSet},
+	zutil_async_loadable::{LocalAsyncLoadable, LocalProgressUpdater},
+};
+
+
+#[tokio::test]
+async fn load() {
+	LocalSet::new()
+		.run_until(async {
+			// Note: `Rc` is `!Send`, so this couldn't be used with `AsyncLoadable`.
+			let loadable = LocalAsyncLoadable::<Rc<u32>, u32>::new();
+
+			let lock = Rc::new(Mutex::new(()));
+			let lock_guard = lock.lock().await;
+
+			let load_handle = loadable
+				.try_load({
+					let lock = Rc::clone(&lock);
+					async move |progress| {
+						progress.update(50);
+						let _ = lock.lock().await;
+						Ok(Rc::new(5))
+					}
+				})
+				.expect("Should not be loading");
+			assert!(loadable.try_load(async |_| Ok(Rc::new(6))).is_none());
+
+			tokio::task::yield_now().await;
+			assert!(loadable.is_loading());
+			assert_eq!(loadable.progress(), Some(50));
+
+			drop(lock_guard);
+			assert_eq!(load_handle.await, Ok(Rc::new(5)));
+			assert_eq!(loadable.wait().await, Ok(Rc::new(5)));
+			assert_eq!(loadable.progress(), None);
+
+			let _: Option<_> = loadable.reset();
+			assert_eq!(loadable.get(), None);
+			assert_eq!(
+				loadable.try_load_or_wait(async |_| Ok(Rc::new(7))).await,
+				Ok(Rc::new(7))
+			);
+		})
+		.await;
+}
+
+#[tokio::test]
+async fn stop_loading() {
+	LocalSet::new()
+		.run_until(async {
+			let loadable = LocalAsyncLoadable::<u32>::new();
+
+			let load_handle = loadable
+				.try_load(async |_| std::future::pending().await)
+				.expect("Should not be loading");
+
+			loadable.stop_loading();
+			assert!(!loadable.is_loading());
+			load_handle.await.expect_err("Should have been stopped");
+			assert_eq!(loadable.get(), None);
+		})
+		.await;
+}
+
+#[tokio::test]
+async fn sync_progress() {
+	LocalSet::new()
+		.run_until(async {
+			let loadable = LocalAsyncLoadable::<u32, u32>::new();
+
+			let load_handle = loadable
+				.try_load({
+					let loadable = loadable.clone_rc();
+					move |progress: LocalProgressUpdater<u32>| {
+						progress.update(1);
+						assert!(loadable.is_loading());
+						async { Ok(5) }
+					}
+				})
+				.expect("Should not be loading");
+			assert_eq!(load_handle.await, Ok(5));
+		})
+		.await;
+}
+
+#[tokio::test]
+async fn update_with_reentrant() {
+	LocalSet::new()
+		.run_until(async {
+			let loadable = LocalAsyncLoadable::<u32, u32>::new();
+
+			let load_handle = loadable
+				.try_load({
+					let loadable = loadable.clone_rc();
+					async move |progress| {
+						progress.update(1);
+						progress.update_with(|progress| {
+							assert_eq!(loadable.progress(), None);
+							*progress += 1;
+						});
+						assert_eq!(loadable.progress(), Some(2));
+						Ok(5)
+					}
+				})
+				.expect("Should not be loading");
+			assert_eq!(load_handle.await, Ok(5));
+		})
+		.await;
+}
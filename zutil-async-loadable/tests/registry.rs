@@ -0,0 +1,62 @@
+//! Registry tests
+
+// Imports
+use {
+	std::{future, io},
+	zutil_async_loadable::{AsyncLoadable, LoadableState, RegistryDump},
+};
+
+
+#[tokio::test]
+async fn dump() {
+	let loaded = AsyncLoadable::<u32>::from_value(5);
+	loaded.register("loaded");
+	let failed = AsyncLoadable::<u32>::from_error(&io::Error::other("Error"));
+	failed.register("failed");
+	let loading = AsyncLoadable::<u32, f32>::new();
+	loading.register("loading");
+	{
+		let dropped = AsyncLoadable::<u32>::new();
+		dropped.register("dropped");
+	}
+
+	let _: Option<_> = loading.try_load(async |progress| {
+		progress.update(0.5);
+		future::pending().await
+	});
+	tokio::task::yield_now().await;
+
+	let dump = RegistryDump::capture();
+	let info = |name| dump.loadables.iter().find(|info| info.name == name);
+	assert!(info("dropped").is_none());
+
+	let loaded_info = info("loaded").expect("Should be registered");
+	assert_eq!(loaded_info.id, loaded.id());
+	assert_eq!(loaded_info.state, LoadableState::Loaded);
+	assert!(loaded_info.age.is_some());
+
+	let failed_info = info("failed").expect("Should be registered");
+	assert!(matches!(failed_info.state, LoadableState::Failed(_)));
+
+	let loading_info = info("loading").expect("Should be registered");
+	assert_eq!(loading_info.state, LoadableState::Loading);
+	assert_eq!(loading_info.generation, loading.generation());
+	assert!(loading_info.loading_for.is_some());
+
+	let text = dump.to_string();
+	assert!(text.contains(r#""loaded" (u32): loaded"#), "{text}");
+
+	#[cfg(feature = "serde")]
+	{
+		let json = serde_json::to_string(&dump).expect("Unable to serialize dump");
+		assert!(json.contains(r#""name":"failed""#), "{json}");
+		assert!(json.contains(r#""state":"Loading""#), "{json}");
+		assert!(json.contains(r#""state":{"Failed":"Error"}"#), "{json}");
+	}
+
+	#[cfg(feature = "json")]
+	assert_eq!(
+		dump.to_json(),
+		serde_json::to_string(&dump).expect("Unable to serialize dump")
+	);
+}
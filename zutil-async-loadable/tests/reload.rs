@@ -0,0 +1,66 @@
+//! Reload tests
+
+// Imports
+use {app_error::AppError, std::io, zutil_async_loadable::AsyncLoadable};
+
+
+#[tokio::test]
+async fn reload_moves() {
+	let loadable = AsyncLoadable::<Vec<u32>>::from_value(vec![1, 2]);
+
+	let value = loadable
+		.try_reload(async |prev, _| {
+			let mut prev = prev.expect("Should have a previous value")?;
+			prev.push(3);
+			Ok(prev)
+		})
+		.expect("Should not be loading")
+		.await
+		.expect("Should be successful");
+	assert_eq!(value, [1, 2, 3]);
+	assert_eq!(loadable.get(), Some(Ok(vec![1, 2, 3])));
+}
+
+#[tokio::test]
+async fn reload_error() {
+	let err = io::Error::other("Error");
+	let loadable = AsyncLoadable::<u32>::from_error(&err);
+
+	let value = loadable
+		.try_reload_cloned(async |prev, _| match prev {
+			Some(Err(_)) => Ok(5),
+			_ => Err(AppError::msg("Expected an error")),
+		})
+		.expect("Should not be loading")
+		.await;
+	assert_eq!(value, Ok(5));
+}
+
+#[tokio::test]
+async fn reload_unloaded() {
+	let loadable = AsyncLoadable::<u32>::new();
+
+	let value = loadable
+		.try_reload(async |prev, _| match prev {
+			Some(_) => Err(AppError::msg("Expected no value")),
+			None => Ok(1),
+		})
+		.expect("Should not be loading")
+		.await;
+	assert_eq!(value, Ok(1));
+}
+
+#[tokio::test]
+async fn reload_invalidates_dependents() {
+	let config = AsyncLoadable::<u32>::from_value(1);
+	let derived = AsyncLoadable::<u32>::from_value(2);
+	derived.add_dependency(&config).expect("Should not be a cycle");
+
+	config
+		.try_reload_cloned(async |prev, _| Ok(prev.expect("Should have a previous value")? + 1))
+		.expect("Should not be loading")
+		.await
+		.expect("Should be successful");
+	assert_eq!(config.get(), Some(Ok(2)));
+	assert_eq!(derived.get(), None);
+}
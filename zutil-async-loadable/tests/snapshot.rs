@@ -0,0 +1,40 @@
+//! Snapshot tests
+
+// Imports
+use {
+	std::io,
+	zutil_async_loadable::{AsyncLoadable, Snapshot},
+};
+
+
+#[tokio::test]
+async fn round_trip() {
+	let loadable = AsyncLoadable::<u32>::from_value(5);
+
+	let snapshot = loadable.snapshot();
+	assert_eq!(snapshot, Snapshot {
+		res:        Some(Ok(5)),
+		is_loading: false,
+		progress:   None,
+	});
+
+	let json = serde_json::to_string(&snapshot).expect("Should serialize");
+	let snapshot = serde_json::from_str::<Snapshot<u32>>(&json).expect("Should deserialize");
+	let loadable = AsyncLoadable::from_snapshot(snapshot);
+	assert_eq!(loadable.get(), Some(Ok(5)));
+}
+
+#[tokio::test]
+async fn error() {
+	let loadable = AsyncLoadable::<u32, f32>::from_error(&io::Error::other("Error"));
+
+	let snapshot = loadable.snapshot();
+	assert_eq!(snapshot.res, Some(Err("Error".to_owned())));
+
+	let loadable = AsyncLoadable::from_snapshot(snapshot);
+	let err = loadable
+		.get()
+		.expect("Should be loaded")
+		.expect_err("Should be an error");
+	assert_eq!(err.to_string(), "Error");
+}
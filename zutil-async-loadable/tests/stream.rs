@@ -0,0 +1,92 @@
+//! Stream tests
+
+// Imports
+use {
+	app_error::AppError,
+	std::sync::Arc,
+	tokio::sync::{Mutex, oneshot},
+	zutil_async_loadable::{AsyncStreamLoadable, StreamStatus},
+};
+
+
+#[tokio::test]
+async fn partial_items() {
+	let stream = AsyncStreamLoadable::<u32>::new();
+	assert_eq!(stream.status(), StreamStatus::Unloaded);
+
+	let lock = Arc::new(Mutex::new(()));
+	let lock_guard = lock.lock().await;
+
+	let load_handle = stream
+		.try_load({
+			let lock = Arc::clone(&lock);
+			async move |writer, _| {
+				writer.extend([1, 2]);
+				let _ = lock.lock().await;
+				writer.push(3);
+				Ok(())
+			}
+		})
+		.expect("Should not be loading");
+
+	assert_eq!(stream.next_items(0).await, [1, 2]);
+	assert_eq!(stream.status(), StreamStatus::Loading);
+
+	drop(lock_guard);
+	assert_eq!(stream.next_items(2).await, [3]);
+
+	load_handle.await.expect("Should be successful");
+	assert!(stream.next_items(3).await.is_empty());
+	assert_eq!(stream.items(), [1, 2, 3]);
+	assert_eq!(stream.status(), StreamStatus::Complete);
+}
+
+#[tokio::test]
+async fn failed() {
+	let stream = AsyncStreamLoadable::<u32>::new();
+
+	let err = AppError::msg("Error");
+	stream
+		.try_load({
+			let err = err.clone();
+			async move |writer, _| {
+				writer.push(1);
+				Err(err)
+			}
+		})
+		.expect("Should not be loading")
+		.await
+		.expect_err("Should be an error");
+
+	assert_eq!(stream.items(), [1]);
+	assert_eq!(stream.status(), StreamStatus::Failed(err));
+
+	stream.reset();
+	assert!(stream.is_empty());
+	assert_eq!(stream.status(), StreamStatus::Unloaded);
+}
+
+#[tokio::test]
+async fn stop_loading() {
+	let stream = AsyncStreamLoadable::<u32>::new();
+
+	let (writer_tx, writer_rx) = oneshot::channel();
+	let _: Option<_> = stream.try_load(async move |writer, _| {
+		writer.push(1);
+		writer_tx
+			.send(writer)
+			.map_err(|_| AppError::msg("Unable to send writer"))?;
+		std::future::pending().await
+	});
+	let writer = writer_rx.await.expect("Should have sent the writer");
+	assert_eq!(stream.status(), StreamStatus::Loading);
+
+	stream.stop_loading();
+	writer.push(2);
+	assert_eq!(stream.items(), [1]);
+	assert!(stream.next_items(1).await.is_empty());
+	assert_eq!(stream.status(), StreamStatus::Stopped);
+
+	stream.reset();
+	assert_eq!(stream.status(), StreamStatus::Unloaded);
+}
@@ -0,0 +1,263 @@
+//! Rotated log file archival
+
+// Imports
+use {
+	crate::rotate::Rotation,
+	flate2::{Compression, write::GzEncoder},
+	std::{
+		fs,
+		io,
+		path::{Path, PathBuf},
+		sync::mpsc,
+		thread,
+		time::SystemTime,
+	},
+};
+
+/// Archiver.
+///
+/// Owns all rotated files of a log file, and manages them in a
+/// background thread, so that logging isn't blocked by them.
+#[derive(Debug)]
+pub struct Archiver {
+	/// Sender for staged files
+	tx: mpsc::Sender<PathBuf>,
+}
+
+impl Archiver {
+	/// Spawns the archiver for the log file at `path`
+	pub fn spawn(path: PathBuf, rotation: Rotation) -> io::Result<Self> {
+		let (tx, rx) = mpsc::channel::<PathBuf>();
+		thread::Builder::new().name("log-archiver".to_owned()).spawn(move || {
+			// Note: We can't log here, since logging might require
+			//       rotating, so we just report to stderr.
+			for staged in rx {
+				if let Err(err) = self::archive(&path, &rotation, &staged) {
+					eprintln!("Unable to archive log file {}: {err}", staged.display());
+				}
+			}
+		})?;
+
+		Ok(Self { tx })
+	}
+
+	/// Archives a staged file.
+	///
+	/// The file becomes the most recent rotated file.
+	pub fn archive(&self, staged: PathBuf) {
+		if let Err(err) = self.tx.send(staged) {
+			eprintln!("Log archiver stopped, leaving {}", err.0.display());
+		}
+	}
+}
+
+/// Returns the path of the `idx`th rotated file of `path`
+fn rotated_path(path: &Path, idx: usize, compressed: bool) -> PathBuf {
+	let mut path = path.to_owned().into_os_string();
+	path.push(format!(".{idx}"));
+	if compressed {
+		path.push(".gz");
+	}
+
+	path.into()
+}
+
+/// Archives `staged` as the most recent rotated file of `path`
+fn archive(path: &Path, rotation: &Rotation, staged: &Path) -> io::Result<()> {
+	let keep = rotation.keep;
+	if keep == 0 {
+		return fs::remove_file(staged);
+	}
+
+	// Shift all rotated files, removing the oldest
+	for compressed in [false, true] {
+		self::ignore_not_found(fs::remove_file(self::rotated_path(path, keep, compressed)))?;
+		for idx in (1..keep).rev() {
+			self::ignore_not_found(fs::rename(
+				self::rotated_path(path, idx, compressed),
+				self::rotated_path(path, idx + 1, compressed),
+			))?;
+		}
+	}
+
+	// Then add the staged file
+	let latest = self::rotated_path(path, 1, false);
+	fs::rename(staged, &latest)?;
+	if rotation.compress {
+		self::compress(&latest, &self::rotated_path(path, 1, true))?;
+	}
+
+	self::prune(path, rotation)
+}
+
+/// Compresses `src` into `dst`, removing `src`
+fn compress(src: &Path, dst: &Path) -> io::Result<()> {
+	// Note: We write to a temporary file first, so that we never leave
+	//       a partially compressed file with the final name.
+	let mut tmp = dst.to_owned().into_os_string();
+	tmp.push(".tmp");
+	let tmp = PathBuf::from(tmp);
+
+	let mut encoder = GzEncoder::new(fs::File::create(&tmp)?, Compression::default());
+	io::copy(&mut fs::File::open(src)?, &mut encoder)?;
+	encoder.finish()?.sync_all()?;
+
+	fs::rename(&tmp, dst)?;
+	fs::remove_file(src)
+}
+
+/// Removes all rotated files older than the max age, or beyond the max total size
+fn prune(path: &Path, rotation: &Rotation) -> io::Result<()> {
+	if rotation.max_age.is_none() && rotation.max_total_size.is_none() {
+		return Ok(());
+	}
+
+	let now = SystemTime::now();
+	let mut total_size = 0;
+	for idx in 1..=rotation.keep {
+		for compressed in [false, true] {
+			let rotated_path = self::rotated_path(path, idx, compressed);
+			let metadata = match fs::metadata(&rotated_path) {
+				Ok(metadata) => metadata,
+				Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+				Err(err) => return Err(err),
+			};
+
+			total_size += metadata.len();
+			let is_too_old = rotation.max_age.is_some_and(|max_age| {
+				metadata
+					.modified()
+					.ok()
+					.and_then(|modified| now.duration_since(modified).ok())
+					.is_some_and(|age| age > max_age)
+			});
+			let is_too_big = rotation
+				.max_total_size
+				.is_some_and(|max_total_size| total_size > max_total_size);
+			if is_too_old || is_too_big {
+				fs::remove_file(&rotated_path)?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Ignores not found errors
+fn ignore_not_found(res: io::Result<()>) -> io::Result<()> {
+	match res {
+		Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+		res => res,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// Imports
+	use {
+		super::*,
+		flate2::read::GzDecoder,
+		std::{io::Read, time::Duration},
+	};
+
+	/// Stages a file with `contents` and archives it
+	fn stage_and_archive(path: &Path, rotation: &Rotation, contents: &str) {
+		let mut staged = path.to_owned().into_os_string();
+		staged.push(".pending");
+		let staged = PathBuf::from(staged);
+		fs::write(&staged, contents).expect("Unable to write staged file");
+		self::archive(path, rotation, &staged).expect("Unable to archive");
+		assert!(!staged.exists());
+	}
+
+	/// Reads a file into a string
+	fn read(path: &Path) -> String {
+		fs::read_to_string(path).expect("Unable to read file")
+	}
+
+	#[test]
+	fn rotated_path() {
+		let path = Path::new("logs/app.log");
+		assert_eq!(super::rotated_path(path, 1, false), Path::new("logs/app.log.1"));
+		assert_eq!(super::rotated_path(path, 12, true), Path::new("logs/app.log.12.gz"));
+	}
+
+	#[test]
+	fn shift() {
+		let dir = crate::temp_dir("archive_shift");
+		let path = dir.join("app.log");
+		let rotation = Rotation::new().keep(2);
+
+		for contents in ["1", "2", "3"] {
+			stage_and_archive(&path, &rotation, contents);
+		}
+
+		assert_eq!(read(&dir.join("app.log.1")), "3");
+		assert_eq!(read(&dir.join("app.log.2")), "2");
+		assert!(!dir.join("app.log.3").exists());
+	}
+
+	#[test]
+	fn keep_none() {
+		let dir = crate::temp_dir("archive_keep_none");
+		let path = dir.join("app.log");
+
+		stage_and_archive(&path, &Rotation::new().keep(0), "1");
+		assert_eq!(fs::read_dir(&dir).expect("Unable to read directory").count(), 0);
+	}
+
+	#[test]
+	fn compress() {
+		let dir = crate::temp_dir("archive_compress");
+		let path = dir.join("app.log");
+		let rotation = Rotation::new().keep(2).compress(true);
+
+		stage_and_archive(&path, &rotation, "1");
+		stage_and_archive(&path, &rotation, "2");
+
+		let decompress = |path: &Path| {
+			let mut contents = String::new();
+			GzDecoder::new(fs::File::open(path).expect("Unable to open file"))
+				.read_to_string(&mut contents)
+				.expect("Unable to decompress file");
+			contents
+		};
+		assert_eq!(decompress(&dir.join("app.log.1.gz")), "2");
+		assert_eq!(decompress(&dir.join("app.log.2.gz")), "1");
+		assert!(!dir.join("app.log.1").exists());
+		assert!(!dir.join("app.log.1.gz.tmp").exists());
+	}
+
+	#[test]
+	fn prune_total_size() {
+		let dir = crate::temp_dir("archive_prune_total_size");
+		let path = dir.join("app.log");
+		let rotation = Rotation::new().max_total_size(8);
+
+		for contents in ["1111", "2222", "3333"] {
+			stage_and_archive(&path, &rotation, contents);
+		}
+
+		assert_eq!(read(&dir.join("app.log.1")), "3333");
+		assert_eq!(read(&dir.join("app.log.2")), "2222");
+		assert!(!dir.join("app.log.3").exists());
+	}
+
+	#[test]
+	fn prune_age() {
+		let dir = crate::temp_dir("archive_prune_age");
+		let path = dir.join("app.log");
+
+		stage_and_archive(&path, &Rotation::new(), "1");
+		let old = SystemTime::now() - Duration::from_hours(1);
+		fs::File::options()
+			.write(true)
+			.open(dir.join("app.log.1"))
+			.and_then(|file| file.set_modified(old))
+			.expect("Unable to set modification time");
+
+		stage_and_archive(&path, &Rotation::new().max_age(Duration::from_mins(1)), "2");
+		assert_eq!(read(&dir.join("app.log.1")), "2");
+		assert!(!dir.join("app.log.2").exists());
+	}
+}
@@ -1,19 +1,31 @@
 //! Logger builder
 
+#[cfg(feature = "config")]
+use crate::config::{self, ConfigError, ConfigWatcher, LoggerConfig, WatchedConfig};
 use {
 	crate::{
 		Logger,
 		LoggerSubscriber,
 		file::{self, FileWriter},
+		format::FileFormat,
+		history::{History, HistoryLayer},
+		memory,
+		non_blocking::OverflowPolicy,
+		panic::PanicHook,
 		pre_init::PreInitLogger,
+		rotate::Rotation,
+		sink::{FileSink, Sink},
 		term,
 	},
 	std::{
 		collections::HashMap,
 		io::{self, Write},
+		iter,
+		path::{Path, PathBuf},
+		sync::Arc,
 	},
 	tracing::Subscriber,
-	tracing_subscriber::{fmt::MakeWriter, prelude::*, registry::LookupSpan},
+	tracing_subscriber::{EnvFilter, fmt::MakeWriter, prelude::*, registry::LookupSpan},
 };
 
 /// Logger builder
@@ -32,6 +44,37 @@ pub struct LoggerBuilder<W, S> {
 
 	/// Filter filters
 	file_filters: HashMap<Option<String>, String>,
+
+	/// File rotation
+	file_rotation: Option<Rotation>,
+
+	/// File format
+	file_format: FileFormat,
+
+	/// File path
+	file_path: Option<PathBuf>,
+
+	/// Limit of the in-memory buffer, in bytes
+	file_buffer_limit: usize,
+
+	/// File sinks
+	file_sinks: HashMap<String, FileSink>,
+
+	/// Queue capacity and policy, if non-blocking
+	file_non_blocking: Option<(usize, OverflowPolicy)>,
+
+	/// Panic hook
+	panic_hook: Option<PanicHook>,
+
+	/// Number of events to keep in the history
+	history_capacity: usize,
+
+	/// History filter
+	history_filter: String,
+
+	/// Watched configuration
+	#[cfg(feature = "config")]
+	watched_config: Option<WatchedConfig>,
 }
 
 impl LoggerBuilder<fn() -> io::Stderr, LoggerSubscriber> {
@@ -47,6 +90,17 @@ impl LoggerBuilder<fn() -> io::Stderr, LoggerSubscriber> {
 			subscriber: LoggerSubscriber::default(),
 			stderr_filters: [(None, "info".to_owned())].into(),
 			file_filters: [(None, "debug".to_owned())].into(),
+			file_rotation: None,
+			file_format: FileFormat::Text,
+			file_path: None,
+			file_buffer_limit: memory::DEFAULT_LIMIT,
+			file_sinks: HashMap::new(),
+			file_non_blocking: None,
+			panic_hook: None,
+			history_capacity: 0,
+			history_filter: "info".to_owned(),
+			#[cfg(feature = "config")]
+			watched_config: None,
 		}
 	}
 }
@@ -103,6 +157,185 @@ impl<W, S> LoggerBuilder<W, S> {
 		self.stderr_filter(key, filter).file_filter(key, filter)
 	}
 
+	/// Sets the rotation of the log file
+	#[must_use]
+	pub fn file_rotation(self, rotation: Rotation) -> Self {
+		Self {
+			file_rotation: Some(rotation),
+			..self
+		}
+	}
+
+	/// Sets the format of the log file.
+	///
+	/// This is independent from the stderr format.
+	#[must_use]
+	pub fn file_format(self, format: FileFormat) -> Self {
+		Self {
+			file_format: format,
+			..self
+		}
+	}
+
+	/// Sets the log file.
+	///
+	/// See [`Logger::set_file`].
+	#[must_use]
+	pub fn file_path(self, path: &Path) -> Self {
+		Self {
+			file_path: Some(path.to_owned()),
+			..self
+		}
+	}
+
+	/// Sets the limit of the in-memory buffer, in bytes.
+	///
+	/// Until a log file is set, logs are kept in memory, so they may be
+	/// written to it. Once this limit is exceeded, the oldest lines are
+	/// dropped, and replaced by a marker line.
+	///
+	/// Defaults to 16 MiB.
+	#[must_use]
+	pub fn file_buffer_limit(self, limit: usize) -> Self {
+		Self {
+			file_buffer_limit: limit,
+			..self
+		}
+	}
+
+	/// Adds a named file sink.
+	///
+	/// If a sink with the same name already exists, it is replaced.
+	/// See [`Logger::sink`].
+	#[must_use]
+	pub fn file_sink(mut self, name: &str, sink: FileSink) -> Self {
+		self.file_sinks.insert(name.to_owned(), sink);
+		self
+	}
+
+	/// Makes the log file non-blocking.
+	///
+	/// Events are queued, up to `capacity`, and written to the file by a
+	/// background thread. Once the queue is full, `policy` decides whether to
+	/// block or drop events.
+	///
+	/// See [`Logger::flush`].
+	#[must_use]
+	pub fn file_non_blocking(self, capacity: usize, policy: OverflowPolicy) -> Self {
+		Self {
+			file_non_blocking: Some((capacity, policy)),
+			..self
+		}
+	}
+
+	/// Installs a panic hook once built.
+	///
+	/// Panics are then logged, instead of only being printed to stderr.
+	/// The previously installed hook is still called afterwards.
+	#[must_use]
+	pub fn panic_hook(self, hook: PanicHook) -> Self {
+		Self {
+			panic_hook: Some(hook),
+			..self
+		}
+	}
+
+	/// Sets the number of events to keep in the history.
+	///
+	/// See [`Logger::recent_events`]. Defaults to `0`, which keeps no events.
+	#[must_use]
+	pub fn history_capacity(self, capacity: usize) -> Self {
+		Self {
+			history_capacity: capacity,
+			..self
+		}
+	}
+
+	/// Sets the filter of the history.
+	///
+	/// Applies to both the kept events and the subscribers.
+	/// Defaults to `info`.
+	///
+	/// Any invalid directives are ignored, with a warning once built.
+	#[must_use]
+	pub fn history_filter(self, filter: &str) -> Self {
+		Self {
+			history_filter: filter.to_owned(),
+			..self
+		}
+	}
+
+	/// Applies a configuration.
+	///
+	/// Any filters in the configuration override existing ones, and are
+	/// themselves overridden by the `RUST_LOG` and `RUST_FILE_LOG` environment
+	/// variables.
+	#[cfg(feature = "config")]
+	#[must_use]
+	pub fn config(mut self, config: &LoggerConfig) -> Self {
+		config::add_filters(
+			&mut self.stderr_filters,
+			config.stderr.filter.as_deref(),
+			&config.stderr.filters,
+		);
+		config::add_filters(
+			&mut self.file_filters,
+			config.file.filter.as_deref(),
+			&config.file.filters,
+		);
+
+		if let Some(path) = &config.file.path {
+			self = self.file_path(path);
+		}
+		for (name, sink) in &config.sinks {
+			self = self.file_sink(name, sink.into());
+		}
+		if let Some(limit) = config.file.buffer_limit {
+			self = self.file_buffer_limit(limit);
+		}
+		if let Some(non_blocking) = &config.file.non_blocking {
+			self = self.file_non_blocking(non_blocking.capacity, non_blocking.policy);
+		}
+		if let Some(format) = config.file.format {
+			self = self.file_format(format);
+		}
+		if let Some(rotation) = &config.file.rotation {
+			self = self.file_rotation(rotation.into());
+		}
+
+		self
+	}
+
+	/// Applies a configuration file, and watches it for changes.
+	///
+	/// Once built, any stderr, file and sink filter changes to the file are
+	/// applied live, on top of the filters set before this call. Any other
+	/// changes, including adding or removing sinks, require re-building the logger.
+	///
+	/// Filters changed at runtime through the [`Logger`] are kept across reloads:
+	/// directives set with [`Logger::set_stderr_filter`] replace the configured
+	/// ones, and directives added with [`Logger::add_stderr_filter`] are applied
+	/// on top of them (and likewise for the file and sink filters).
+	#[cfg(feature = "config")]
+	pub fn watch_config(self, path: &Path) -> Result<Self, ConfigError> {
+		let config = LoggerConfig::from_file(path)?;
+		let watched_config = WatchedConfig {
+			path:        path.to_owned(),
+			stderr_base: self.stderr_filters.clone(),
+			file_base:   self.file_filters.clone(),
+			sink_base:   self
+				.file_sinks
+				.iter()
+				.map(|(name, sink)| (name.clone(), sink.filter.clone()))
+				.collect(),
+		};
+
+		Ok(Self {
+			watched_config: Some(watched_config),
+			..self.config(&config)
+		})
+	}
+
 	/// Builds the logger
 	#[must_use]
 	pub fn build(self) -> Logger
@@ -111,13 +344,46 @@ impl<W, S> LoggerBuilder<W, S> {
 		S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync + 'static,
 	{
 		// Then initialize our logging
-		let file_writer = FileWriter::memory();
+		let file_writer = self::file_writer(self.file_rotation, self.file_buffer_limit, self.file_non_blocking);
 
 		// Note: Due to [this issue](https://github.com/tokio-rs/tracing/issues/1817),
 		//       the order here matters, and the stderr ones must be last.
-		let file_layer = file::layer(file_writer.clone(), self::filters_iter(&self.file_filters));
-		let term_layer = term::layer(self.stderr.clone(), self::filters_iter(&self.stderr_filters));
-		let subscriber = self.subscriber.with(file_layer).with(term_layer);
+		let mut sinks = HashMap::new();
+		let mut sink_layers = vec![];
+		for (name, sink) in &self.file_sinks {
+			let sink_writer = self::file_writer(sink.rotation.clone(), self.file_buffer_limit, sink.non_blocking);
+			let (sink_layer, sink_filter) = file::layer(sink_writer.clone(), sink.format, &sink.filter);
+			sink_layers.push(sink_layer.boxed());
+			sinks.insert(
+				name.clone(),
+				Sink::new(name.clone(), sink_writer, Arc::new(sink_filter)),
+			);
+		}
+		let (file_layer, file_filter) = file::layer(
+			file_writer.clone(),
+			self.file_format,
+			&crate::get_env_filters("RUST_FILE_LOG", self::filters_iter(&self.file_filters)),
+		);
+		let (term_layer, stderr_filter) = term::layer(self.stderr.clone(), self::filters_iter(&self.stderr_filters));
+		let history = Arc::new(History::new(self.history_capacity));
+		let history_filter = EnvFilter::builder().parse(&self.history_filter).unwrap_or_else(|err| {
+			tracing::warn!(
+				"Ignoring invalid directives in history filter {:?}: {err}",
+				self.history_filter
+			);
+			EnvFilter::builder().parse_lossy(&self.history_filter)
+		});
+		let history_layer = HistoryLayer::new(Arc::clone(&history)).with_filter(history_filter);
+
+		// Note: An empty layer vector is never interested in any events, which
+		//       would disable all of the other layers, so we skip it instead.
+		let sink_layers = (!sink_layers.is_empty()).then_some(sink_layers);
+		let subscriber = self
+			.subscriber
+			.with(history_layer)
+			.with(sink_layers)
+			.with(file_layer)
+			.with(term_layer);
 		if let Err(err) = subscriber.try_init() {
 			eprintln!("Failed to set global logger: {err}");
 		}
@@ -132,7 +398,57 @@ impl<W, S> LoggerBuilder<W, S> {
 
 		tracing::info!("Successfully initialized logger");
 
-		Logger { file_writer }
+		let stderr_filter = Arc::new(stderr_filter);
+		let file_filter = Arc::new(file_filter);
+
+		#[cfg(feature = "config")]
+		let config_watcher = self.watched_config.and_then(|watched| {
+			let sink_filters = sinks
+				.iter()
+				.map(|(name, sink)| (name.clone(), Arc::clone(sink.reload_filter())))
+				.collect();
+			match ConfigWatcher::spawn(
+				watched,
+				Arc::clone(&stderr_filter),
+				Arc::clone(&file_filter),
+				sink_filters,
+			) {
+				Ok(watcher) => Some(watcher),
+				Err(err) => {
+					tracing::warn!("Unable to watch log configuration: {err}");
+					None
+				},
+			}
+		});
+
+		if let Some(hook) = self.panic_hook {
+			let writers = iter::once(file_writer.clone())
+				.chain(sinks.values().map(|sink| sink.writer().clone()))
+				.collect();
+			hook.install(writers);
+		}
+
+		let logger = Logger {
+			file_writer,
+			stderr_filter,
+			file_filter,
+			history,
+			sinks,
+			#[cfg(feature = "config")]
+			_config_watcher: config_watcher,
+		};
+		if let Some(path) = &self.file_path {
+			logger.set_file(Some(path));
+		}
+		for (name, sink) in &self.file_sinks {
+			if let Some(path) = &sink.path &&
+				let Some(sink) = logger.sink(name)
+			{
+				sink.set_file(Some(path));
+			}
+		}
+
+		logger
 	}
 }
 
@@ -142,7 +458,27 @@ impl Default for LoggerBuilder<fn() -> io::Stderr, LoggerSubscriber> {
 	}
 }
 
+/// Creates a file writer, non-blocking if `non_blocking` is set
+fn file_writer(
+	rotation: Option<Rotation>,
+	buffer_limit: usize,
+	non_blocking: Option<(usize, OverflowPolicy)>,
+) -> FileWriter {
+	let file_writer = FileWriter::memory(rotation, buffer_limit);
+	let Some((capacity, policy)) = non_blocking else {
+		return file_writer;
+	};
+
+	file_writer
+		.clone()
+		.non_blocking(capacity, policy)
+		.unwrap_or_else(|err| {
+			tracing::warn!("Unable to spawn log writer, logging to file will block: {err}");
+			file_writer
+		})
+}
+
 /// Converts the filters field into an iterator
-fn filters_iter(filters: &HashMap<Option<String>, String>) -> impl Iterator<Item = (Option<&'_ str>, &'_ str)> {
+pub fn filters_iter(filters: &HashMap<Option<String>, String>) -> impl Iterator<Item = (Option<&'_ str>, &'_ str)> {
 	filters.iter().map(|(key, value)| (key.as_deref(), value.as_str()))
 }
@@ -0,0 +1,453 @@
+//! Logger configuration
+
+// Imports
+use {
+	crate::{
+		filter::ReloadFilter,
+		format::FileFormat,
+		non_blocking::OverflowPolicy,
+		rotate::{Rotation, RotationPeriod},
+		sink::FileSink,
+	},
+	serde::Deserialize,
+	std::{
+		collections::HashMap,
+		error::Error,
+		fmt,
+		fs,
+		io,
+		path::{Path, PathBuf},
+		sync::{Arc, mpsc},
+		thread,
+		time::{Duration, SystemTime},
+	},
+};
+
+/// Interval between checks for changes of a watched configuration file
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Logger configuration.
+///
+/// Usually loaded from a TOML file, such as:
+///
+/// ```toml
+/// [stderr]
+/// filter = "info"
+/// filters = { "my_crate::net" = "debug" }
+///
+/// [file]
+/// path = "app.log"
+/// format = "json"
+/// rotation = { max_size = 10_000_000, keep = 3, compress = true }
+///
+/// [sinks.audit]
+/// filter = "audit=info"
+/// path = "audit.log"
+/// ```
+#[derive(Clone, Default, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggerConfig {
+	/// Stderr
+	pub stderr: StderrConfig,
+
+	/// File
+	pub file: FileConfig,
+
+	/// File sinks, by name
+	pub sinks: HashMap<String, SinkConfig>,
+}
+
+impl LoggerConfig {
+	/// Parses a configuration from TOML
+	pub fn from_toml(s: &str) -> Result<Self, ConfigError> {
+		toml::from_str(s).map_err(ConfigError::Parse)
+	}
+
+	/// Loads a configuration from a TOML file
+	pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+		let s = fs::read_to_string(path).map_err(ConfigError::Read)?;
+		Self::from_toml(&s)
+	}
+}
+
+/// Stderr configuration
+#[derive(Clone, Default, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StderrConfig {
+	/// Default filter
+	pub filter: Option<String>,
+
+	/// Filters, by target
+	pub filters: HashMap<String, String>,
+}
+
+/// File configuration
+#[derive(Clone, Default, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileConfig {
+	/// Default filter
+	pub filter: Option<String>,
+
+	/// Filters, by target
+	pub filters: HashMap<String, String>,
+
+	/// Path of the log file
+	pub path: Option<PathBuf>,
+
+	/// Limit of the in-memory buffer, in bytes
+	pub buffer_limit: Option<usize>,
+
+	/// Format
+	pub format: Option<FileFormat>,
+
+	/// Non-blocking writing
+	pub non_blocking: Option<NonBlockingConfig>,
+
+	/// Rotation
+	pub rotation: Option<RotationConfig>,
+}
+
+/// File sink configuration.
+///
+/// See [`FileSink`](crate::FileSink).
+#[derive(Clone, Default, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SinkConfig {
+	/// Filter
+	pub filter: Option<String>,
+
+	/// Path of the log file
+	pub path: Option<PathBuf>,
+
+	/// Format
+	pub format: Option<FileFormat>,
+
+	/// Non-blocking writing
+	pub non_blocking: Option<NonBlockingConfig>,
+
+	/// Rotation
+	pub rotation: Option<RotationConfig>,
+}
+
+impl From<&SinkConfig> for FileSink {
+	fn from(config: &SinkConfig) -> Self {
+		let mut sink = Self::new();
+		if let Some(filter) = &config.filter {
+			sink = sink.filter(filter);
+		}
+		if let Some(path) = &config.path {
+			sink = sink.path(path);
+		}
+		if let Some(format) = config.format {
+			sink = sink.format(format);
+		}
+		if let Some(non_blocking) = &config.non_blocking {
+			sink = sink.non_blocking(non_blocking.capacity, non_blocking.policy);
+		}
+		if let Some(rotation) = &config.rotation {
+			sink = sink.rotation(rotation.into());
+		}
+
+		sink
+	}
+}
+
+/// Non-blocking writing configuration.
+///
+/// See [`LoggerBuilder::file_non_blocking`](crate::LoggerBuilder::file_non_blocking).
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NonBlockingConfig {
+	/// Queue capacity
+	pub capacity: usize,
+
+	/// Policy for when the queue is full
+	#[serde(default)]
+	pub policy: OverflowPolicy,
+}
+
+/// Rotation configuration.
+///
+/// See [`Rotation`] for details on each option.
+#[derive(Clone, Default, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RotationConfig {
+	/// Maximum size of a log file, in bytes
+	pub max_size: Option<u64>,
+
+	/// Period
+	pub period: Option<RotationPeriod>,
+
+	/// Number of rotated files to keep
+	pub keep: Option<usize>,
+
+	/// Whether to compress rotated files
+	pub compress: bool,
+
+	/// Maximum age of rotated files, in seconds
+	pub max_age_secs: Option<u64>,
+
+	/// Maximum total size of rotated files, in bytes
+	pub max_total_size: Option<u64>,
+}
+
+impl From<&RotationConfig> for Rotation {
+	fn from(config: &RotationConfig) -> Self {
+		let mut rotation = Self::new().compress(config.compress);
+		if let Some(max_size) = config.max_size {
+			rotation = rotation.max_size(max_size);
+		}
+		if let Some(period) = config.period {
+			rotation = rotation.period(period);
+		}
+		if let Some(keep) = config.keep {
+			rotation = rotation.keep(keep);
+		}
+		if let Some(max_age_secs) = config.max_age_secs {
+			rotation = rotation.max_age(Duration::from_secs(max_age_secs));
+		}
+		if let Some(max_total_size) = config.max_total_size {
+			rotation = rotation.max_total_size(max_total_size);
+		}
+
+		rotation
+	}
+}
+
+/// Adds the filters of a sink to `filters`
+pub fn add_filters(
+	filters: &mut HashMap<Option<String>, String>,
+	filter: Option<&str>,
+	targets: &HashMap<String, String>,
+) {
+	if let Some(filter) = filter {
+		filters.insert(None, filter.to_owned());
+	}
+	for (target, filter) in targets {
+		filters.insert(Some(target.clone()), filter.clone());
+	}
+}
+
+/// Watched configuration
+#[derive(Clone, Debug)]
+pub struct WatchedConfig {
+	/// Path of the configuration file
+	pub path: PathBuf,
+
+	/// Stderr filters from before the configuration was applied
+	pub stderr_base: HashMap<Option<String>, String>,
+
+	/// File filters from before the configuration was applied
+	pub file_base: HashMap<Option<String>, String>,
+
+	/// Sink filters from before the configuration was applied, by name
+	pub sink_base: HashMap<String, String>,
+}
+
+/// Configuration watcher.
+///
+/// Polls the configuration file for changes in a background thread,
+/// and applies any filter changes as the base directives of each filter,
+/// keeping any runtime changes. The thread stops once this is dropped.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+	/// Stop sender
+	_stop_tx: mpsc::Sender<()>,
+}
+
+impl ConfigWatcher {
+	/// Spawns the watcher.
+	///
+	/// On each change, the filters of the configuration are added on top of
+	/// the base filters of `watched`.
+	///
+	/// Sink filters replace the base filter of the sink instead, since
+	/// they don't have per-target filters.
+	pub fn spawn(
+		watched: WatchedConfig,
+		stderr_filter: Arc<ReloadFilter>,
+		file_filter: Arc<ReloadFilter>,
+		sink_filters: HashMap<String, Arc<ReloadFilter>>,
+	) -> io::Result<Self> {
+		let WatchedConfig {
+			path,
+			stderr_base,
+			file_base,
+			sink_base,
+		} = watched;
+		let (stop_tx, stop_rx) = mpsc::channel();
+		let mut modified = self::modified(&path);
+		thread::Builder::new()
+			.name("log-config-watcher".to_owned())
+			.spawn(move || {
+				while stop_rx.recv_timeout(POLL_INTERVAL) == Err(mpsc::RecvTimeoutError::Timeout) {
+					let cur_modified = self::modified(&path);
+					if cur_modified == modified {
+						continue;
+					}
+					modified = cur_modified;
+
+					let config = match LoggerConfig::from_file(&path) {
+						Ok(config) => config,
+						Err(err) => {
+							tracing::warn!("Unable to reload log configuration {path:?}: {err}");
+							continue;
+						},
+					};
+
+					let mut stderr_filters = stderr_base.clone();
+					self::add_filters(
+						&mut stderr_filters,
+						config.stderr.filter.as_deref(),
+						&config.stderr.filters,
+					);
+					let stderr_directives =
+						crate::get_env_filters("RUST_LOG", crate::builder::filters_iter(&stderr_filters));
+					if let Err(err) = stderr_filter.set_base(&stderr_directives) {
+						tracing::warn!("Unable to apply stderr filter {stderr_directives:?}: {err}");
+					}
+
+					let mut file_filters = file_base.clone();
+					self::add_filters(&mut file_filters, config.file.filter.as_deref(), &config.file.filters);
+					let file_directives =
+						crate::get_env_filters("RUST_FILE_LOG", crate::builder::filters_iter(&file_filters));
+					if let Err(err) = file_filter.set_base(&file_directives) {
+						tracing::warn!("Unable to apply file filter {file_directives:?}: {err}");
+					}
+
+					for (name, sink_filter) in &sink_filters {
+						let sink_directives = config
+							.sinks
+							.get(name)
+							.and_then(|sink| sink.filter.clone())
+							.or_else(|| sink_base.get(name).cloned())
+							.unwrap_or_else(|| FileSink::new().filter);
+						if let Err(err) = sink_filter.set_base(&sink_directives) {
+							tracing::warn!("Unable to apply filter {sink_directives:?} of sink {name:?}: {err}");
+						}
+					}
+
+					tracing::info!("Reloaded log configuration {path:?}");
+				}
+			})?;
+
+		Ok(Self { _stop_tx: stop_tx })
+	}
+}
+
+/// Returns the modification time of `path`, if any
+fn modified(path: &Path) -> Option<SystemTime> {
+	fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Configuration error
+#[derive(Debug)]
+pub enum ConfigError {
+	/// Unable to read the configuration file
+	Read(io::Error),
+
+	/// Unable to parse the configuration
+	Parse(toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Read(_) => f.write_str("Unable to read log configuration"),
+			Self::Parse(_) => f.write_str("Unable to parse log configuration"),
+		}
+	}
+}
+
+impl Error for ConfigError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			Self::Read(err) => Some(err),
+			Self::Parse(err) => Some(err),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// Imports
+	use {
+		super::*,
+		std::{fs::File, time::Instant},
+		tracing_subscriber::{EnvFilter, Registry, reload},
+	};
+
+	/// Creates a filter with `directives`, along with it's layer
+	fn filter(directives: &str) -> (Arc<ReloadFilter>, reload::Layer<EnvFilter, Registry>) {
+		let (filter, layer) = ReloadFilter::new(EnvFilter::builder(), directives);
+		(Arc::new(filter), layer)
+	}
+
+	/// Waits until `f` returns true
+	fn wait_until(mut f: impl FnMut() -> bool) -> bool {
+		let start = Instant::now();
+		while !f() {
+			if start.elapsed() > Duration::from_secs(5) {
+				return false;
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+
+		true
+	}
+
+	#[test]
+	fn watch_reloads() {
+		let path = crate::temp_dir("config-watch").join("log.toml");
+		fs::write(
+			&path,
+			"[stderr]\nfilter = \"info\"\n[sinks.audit]\nfilter = \"audit=info\"\n",
+		)
+		.expect("Unable to write");
+
+		let (stderr_filter, _stderr_layer) = self::filter("info");
+		let (file_filter, _file_layer) = self::filter("debug");
+		let (sink_filter, _sink_layer) = self::filter("audit=info");
+		let watched = WatchedConfig {
+			path:        path.clone(),
+			stderr_base: HashMap::new(),
+			file_base:   HashMap::from([(None, "debug".to_owned())]),
+			sink_base:   HashMap::new(),
+		};
+		let _watcher = ConfigWatcher::spawn(
+			watched,
+			Arc::clone(&stderr_filter),
+			Arc::clone(&file_filter),
+			HashMap::from([("audit".to_owned(), Arc::clone(&sink_filter))]),
+		)
+		.expect("Unable to spawn watcher");
+		stderr_filter.add("app=warn").expect("Unable to add directives");
+
+		// Note: The modification time might not have enough resolution to
+		//       notice the change, so we bump it explicitly.
+		fs::write(
+			&path,
+			"[stderr]\nfilters = { app_net = \"trace\" }\n[sinks.audit]\nfilter = \"audit=trace\"\n",
+		)
+		.expect("Unable to write");
+		File::options()
+			.write(true)
+			.open(&path)
+			.and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(1)))
+			.expect("Unable to set modification time");
+
+		assert!(
+			wait_until(|| sink_filter.directives() == "audit=trace"),
+			"{}",
+			sink_filter.directives()
+		);
+		let stderr_directives = stderr_filter.directives();
+		assert!(stderr_directives.contains("app_net=trace"), "{stderr_directives}");
+		assert!(stderr_directives.ends_with("app=warn"), "{stderr_directives}");
+		assert!(
+			file_filter.directives().contains("debug"),
+			"{}",
+			file_filter.directives()
+		);
+	}
+}
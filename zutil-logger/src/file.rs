@@ -2,29 +2,92 @@
 
 // Imports
 use {
+	crate::{
+		filter::ReloadFilter,
+		format::{FileFormat, Logfmt},
+		memory::MemoryBuffer,
+		non_blocking::{OverflowPolicy, Queue, QueueWriter},
+		rotate::{LogFile, Rotation},
+	},
 	std::{
-		fs,
 		io::{self, Write},
+		path::Path,
 		sync::{
 			Arc,
 			nonpoison::{Mutex, MutexGuard},
 		},
+		thread,
+		time::{Duration, Instant},
 	},
 	tracing::Subscriber,
 	tracing_subscriber::{EnvFilter, Layer, fmt::MakeWriter, registry::LookupSpan},
 };
 
+/// Time to wait for the backend while panicking.
+///
+/// The panicking thread might be the one holding the backend locked,
+/// so we can't wait for it indefinitely.
+const PANIC_LOCK_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// File layer writer.
 #[derive(Clone, Debug)]
 pub struct FileWriter {
 	kind: Arc<Mutex<FileWriterKind>>,
+
+	/// Rotation of the log files
+	rotation: Option<Rotation>,
+
+	/// Queue, if non-blocking
+	queue: Option<Arc<Queue>>,
 }
 
 impl FileWriter {
-	/// Creates a new file writer, writing to memory
-	pub fn memory() -> Self {
+	/// Creates a new file writer, writing to memory, up to `limit` bytes
+	pub fn memory(rotation: Option<Rotation>, limit: usize) -> Self {
 		Self {
-			kind: Arc::new(Mutex::new(FileWriterKind::Memory(vec![]))),
+			kind: Arc::new(Mutex::new(FileWriterKind::Memory(MemoryBuffer::new(limit)))),
+			rotation,
+			queue: None,
+		}
+	}
+
+	/// Makes this file writer non-blocking.
+	///
+	/// Writes are queued, up to `capacity`, and written by a background thread.
+	pub fn non_blocking(self, capacity: usize, policy: OverflowPolicy) -> io::Result<Self> {
+		let queue = Queue::spawn(self.clone(), capacity, policy)?;
+		Ok(Self {
+			queue: Some(Arc::new(queue)),
+			..self
+		})
+	}
+
+	/// Flushes this file writer.
+	///
+	/// If non-blocking, waits for all queued writes to be written.
+	pub fn flush(&self) -> io::Result<()> {
+		match &self.queue {
+			Some(queue) => {
+				queue.flush();
+				Ok(())
+			},
+			None => self.kind.lock().flush(),
+		}
+	}
+
+	/// Flushes this file writer, waiting at most `timeout`.
+	///
+	/// If non-blocking, waits for all queued writes to be written.
+	pub fn flush_timeout(&self, timeout: Duration) -> io::Result<()> {
+		match &self.queue {
+			Some(queue) => queue.flush_timeout(timeout),
+			None => match self::lock_timeout(&self.kind, timeout) {
+				Some(mut kind) => kind.flush(),
+				None => Err(io::Error::new(
+					io::ErrorKind::TimedOut,
+					"Timed out locking the log file",
+				)),
+			},
 		}
 	}
 
@@ -32,15 +95,19 @@ impl FileWriter {
 	///
 	/// If this was writing into memory, writes all captured
 	/// data into the file
-	pub fn set_file(&self, mut file: fs::File) {
+	pub fn set_file(&self, path: &Path) -> io::Result<()> {
+		let mut file = LogFile::create(path, self.rotation.clone())?;
+
 		let mut kind = self.kind.lock();
-		if let FileWriterKind::Memory(bytes) = &*kind &&
-			let Err(err) = file.write_all(bytes)
+		if let FileWriterKind::Memory(buffer) = &*kind &&
+			let Err(err) = buffer.write_into(&mut file)
 		{
 			tracing::warn!("Unable to write to log file: {err}");
 		}
 
 		*kind = FileWriterKind::File(file);
+
+		Ok(())
 	}
 
 	/// Sets this file writer to become empty
@@ -50,10 +117,33 @@ impl FileWriter {
 }
 
 impl<'a> MakeWriter<'a> for FileWriter {
-	type Writer = FileWriterKindGuard<'a>;
+	type Writer = FileWriterGuard<'a>;
 
 	fn make_writer(&'a self) -> Self::Writer {
-		FileWriterKindGuard(self.kind.lock())
+		match &self.queue {
+			Some(queue) => FileWriterGuard::Queue(QueueWriter(queue)),
+			None => match thread::panicking() {
+				true => match self::lock_timeout(&self.kind, PANIC_LOCK_TIMEOUT) {
+					Some(kind) => FileWriterGuard::Kind(FileWriterKindGuard(kind)),
+					None => FileWriterGuard::None,
+				},
+				false => FileWriterGuard::Kind(FileWriterKindGuard(self.kind.lock())),
+			},
+		}
+	}
+}
+
+/// Locks `mutex`, waiting at most `timeout`
+fn lock_timeout<T>(mutex: &Mutex<T>, timeout: Duration) -> Option<MutexGuard<'_, T>> {
+	let deadline = Instant::now() + timeout;
+	loop {
+		if let Ok(guard) = mutex.try_lock() {
+			break Some(guard);
+		}
+		if Instant::now() >= deadline {
+			break None;
+		}
+		thread::sleep(Duration::from_millis(1));
 	}
 }
 
@@ -61,51 +151,145 @@ impl<'a> MakeWriter<'a> for FileWriter {
 #[derive(Debug)]
 enum FileWriterKind {
 	/// File
-	File(fs::File),
+	File(LogFile),
 
 	/// Memory
-	Memory(Vec<u8>),
+	Memory(MemoryBuffer),
 
 	/// None
 	None,
 }
 
-/// Guard that implements `io::Write` for `FileWriter` to return
+impl io::Write for FileWriterKind {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		match self {
+			Self::File(file) => file.write(buf),
+			Self::Memory(buffer) => buffer.write(buf),
+			Self::None => Ok(0),
+		}
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match self {
+			Self::File(file) => file.flush(),
+			Self::Memory(buffer) => buffer.flush(),
+			Self::None => Ok(()),
+		}
+	}
+}
+
+/// Writer that `FileWriter` returns
+#[derive(Debug)]
+pub enum FileWriterGuard<'a> {
+	/// Locked backend
+	Kind(FileWriterKindGuard<'a>),
+
+	/// Queue
+	Queue(QueueWriter<'a>),
+
+	/// Unable to lock the backend while panicking
+	None,
+}
+
+impl io::Write for FileWriterGuard<'_> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		match self {
+			Self::Kind(guard) => guard.write(buf),
+			Self::Queue(writer) => writer.write(buf),
+			Self::None => Ok(buf.len()),
+		}
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match self {
+			Self::Kind(guard) => guard.flush(),
+			Self::Queue(writer) => writer.flush(),
+			Self::None => Ok(()),
+		}
+	}
+}
+
+/// Guard that implements `io::Write` for the locked backend
 #[derive(Debug)]
 pub struct FileWriterKindGuard<'a>(MutexGuard<'a, FileWriterKind>);
 
 impl io::Write for FileWriterKindGuard<'_> {
 	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-		match &mut *self.0 {
-			FileWriterKind::File(file) => file.write(buf),
-			FileWriterKind::Memory(items) => items.write(buf),
-			FileWriterKind::None => Ok(0),
-		}
+		self.0.write(buf)
 	}
 
 	fn flush(&mut self) -> io::Result<()> {
-		match &mut *self.0 {
-			FileWriterKind::File(file) => file.flush(),
-			FileWriterKind::Memory(items) => items.flush(),
-			FileWriterKind::None => Ok(()),
-		}
+		self.0.flush()
 	}
 }
 
-/// Creates the file layer
-pub fn layer<S>(
-	writer: FileWriter,
-	default_filters: impl IntoIterator<Item = (Option<&'_ str>, &'_ str)>,
-) -> impl Layer<S>
+/// Creates a file layer
+pub fn layer<S>(writer: FileWriter, format: FileFormat, directives: &str) -> (impl Layer<S>, ReloadFilter)
 where
 	S: Subscriber + for<'a> LookupSpan<'a>,
 {
-	// Then create the layer
-	let env = super::get_env_filters("RUST_FILE_LOG", default_filters);
-	let layer = tracing_subscriber::fmt::layer()
-		.with_writer(writer)
-		.with_ansi(false)
-		.with_filter(EnvFilter::builder().parse_lossy(env));
-
-	Some(layer)
+	let layer = tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(false);
+	let layer = match format {
+		FileFormat::Text => layer.boxed(),
+		FileFormat::Json => layer.json().with_current_span(true).with_span_list(true).boxed(),
+		FileFormat::Logfmt => layer.event_format(Logfmt).boxed(),
+	};
+
+	let (filter, filter_layer) = ReloadFilter::new(EnvFilter::builder(), directives);
+
+	(layer.with_filter(filter_layer), filter)
+}
+
+#[cfg(test)]
+mod tests {
+	// Imports
+	use {
+		super::*,
+		crate::memory,
+		serde_json::Value,
+		std::fs,
+		tracing_subscriber::{Registry, layer::SubscriberExt},
+	};
+
+	#[test]
+	fn json() {
+		let path = crate::temp_dir("file-json").join("app.log");
+		let writer = FileWriter::memory(None, memory::DEFAULT_LIMIT);
+		writer.set_file(&path).expect("Unable to set file");
+
+		let (layer, _filter) = self::layer(writer, FileFormat::Json, "info");
+		tracing::subscriber::with_default(Registry::default().with(layer), || {
+			tracing::info!(target: "app", count = 5, "Hello");
+
+			let outer = tracing::info_span!("outer", id = 1);
+			let _outer = outer.enter();
+			let inner = tracing::info_span!("inner", kind = "abc");
+			let _inner = inner.enter();
+			tracing::warn!(target: "app", "Inside");
+		});
+
+		let lines = fs::read_to_string(&path)
+			.expect("Unable to read log file")
+			.lines()
+			.map(|line| serde_json::from_str::<Value>(line).expect("Line wasn't valid json"))
+			.collect::<Vec<_>>();
+		let [hello, inside] = &*lines else {
+			panic!("Expected 2 lines, found {lines:?}");
+		};
+
+		assert_eq!(hello["level"], "INFO");
+		assert_eq!(hello["target"], "app");
+		assert_eq!(hello["fields"]["message"], "Hello");
+		assert_eq!(hello["fields"]["count"], 5);
+		assert!(hello.get("span").is_none(), "{hello}");
+
+		assert_eq!(inside["level"], "WARN");
+		assert_eq!(inside["fields"]["message"], "Inside");
+		assert_eq!(inside["span"]["name"], "inner");
+		assert_eq!(inside["span"]["kind"], "abc");
+		assert_eq!(inside["spans"][0]["name"], "outer");
+		assert_eq!(inside["spans"][0]["id"], 1);
+		assert_eq!(inside["spans"][1]["name"], "inner");
+		assert_eq!(inside["spans"][1]["kind"], "abc");
+	}
 }
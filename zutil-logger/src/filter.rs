@@ -0,0 +1,246 @@
+//! Reloadable filters
+
+// Imports
+use {
+	itertools::Itertools,
+	std::{error::Error, fmt, iter, sync::nonpoison::Mutex},
+	tracing::Subscriber,
+	tracing_subscriber::{
+		EnvFilter,
+		filter::{Builder, ParseError},
+		reload,
+	},
+};
+
+/// Reload function
+type Reload = dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync;
+
+/// Reloadable filter.
+///
+/// Keeps the directives of a filter, so they may be replaced or
+/// extended at runtime.
+///
+/// Runtime changes are kept separately from the base directives, so
+/// that the base directives may be replaced (e.g. by a configuration
+/// file) without losing them.
+pub struct ReloadFilter {
+	/// Builder
+	builder: Builder,
+
+	/// Current directives
+	directives: Mutex<Directives>,
+
+	/// Reload
+	reload: Box<Reload>,
+}
+
+impl ReloadFilter {
+	/// Creates a new reloadable filter.
+	///
+	/// Since these are the initial directives, any invalid directives are
+	/// removed, with a warning.
+	pub fn new<S>(builder: Builder, directives: &str) -> (Self, reload::Layer<EnvFilter, S>)
+	where
+		S: Subscriber,
+	{
+		// Note: We remove the invalid directives, instead of just ignoring them,
+		//       so that the directives may be extended later.
+		let (valid, invalid) = directives
+			.split(',')
+			.filter(|directive| !directive.is_empty())
+			.partition::<Vec<_>, _>(|directive| builder.parse(directive).is_ok());
+		if !invalid.is_empty() {
+			tracing::warn!("Ignoring invalid filter directives: {invalid:?}");
+		}
+		let directives = valid.join(",");
+
+		let (layer, handle) = reload::Layer::new(builder.parse_lossy(&directives));
+		let filter = Self {
+			builder,
+			directives: Mutex::new(Directives {
+				base:     directives,
+				replaced: None,
+				added:    vec![],
+			}),
+			reload: Box::new(move |filter| handle.reload(filter)),
+		};
+
+		(filter, layer)
+	}
+
+	/// Returns the current directives
+	pub fn directives(&self) -> String {
+		self.directives.lock().effective()
+	}
+
+	/// Replaces all directives
+	pub fn set(&self, directives: &str) -> Result<(), FilterError> {
+		self.update(|cur_directives| {
+			cur_directives.replaced = Some(directives.to_owned());
+			cur_directives.added.clear();
+		})
+	}
+
+	/// Adds directives, overriding any existing directives for the same targets
+	pub fn add(&self, directives: &str) -> Result<(), FilterError> {
+		self.update(|cur_directives| cur_directives.added.push(directives.to_owned()))
+	}
+
+	/// Replaces the base directives.
+	///
+	/// Any directives set or added with [`Self::set`] or [`Self::add`] are kept,
+	/// and still override the base directives.
+	#[cfg(feature = "config")]
+	pub fn set_base(&self, directives: &str) -> Result<(), FilterError> {
+		self.update(|cur_directives| directives.clone_into(&mut cur_directives.base))
+	}
+
+	/// Updates the directives with `f` and reloads the filter.
+	///
+	/// If unable to, the directives are left unchanged.
+	fn update(&self, f: impl FnOnce(&mut Directives)) -> Result<(), FilterError> {
+		let mut cur_directives = self.directives.lock();
+		let mut new_directives = cur_directives.clone();
+		f(&mut new_directives);
+
+		let filter = self
+			.builder
+			.parse(new_directives.effective())
+			.map_err(FilterError::Parse)?;
+		(self.reload)(filter).map_err(FilterError::Reload)?;
+		*cur_directives = new_directives;
+
+		Ok(())
+	}
+}
+
+impl fmt::Debug for ReloadFilter {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ReloadFilter")
+			.field("directives", &self.directives())
+			.finish_non_exhaustive()
+	}
+}
+
+/// Directives
+#[derive(Clone, Debug)]
+struct Directives {
+	/// Base directives
+	base: String,
+
+	/// Directives replacing the base directives
+	replaced: Option<String>,
+
+	/// Added directives
+	added: Vec<String>,
+}
+
+impl Directives {
+	/// Returns the effective directives
+	fn effective(&self) -> String {
+		iter::once(self.replaced.as_deref().unwrap_or(&self.base))
+			.chain(self.added.iter().map(String::as_str))
+			.filter(|directives| !directives.is_empty())
+			.join(",")
+	}
+}
+
+/// Filter error
+#[derive(Debug)]
+pub enum FilterError {
+	/// Unable to parse the directives
+	Parse(ParseError),
+
+	/// Unable to reload the filter
+	Reload(reload::Error),
+}
+
+impl fmt::Display for FilterError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Parse(_) => f.write_str("Unable to parse filter directives"),
+			Self::Reload(_) => f.write_str("Unable to reload filter"),
+		}
+	}
+}
+
+impl Error for FilterError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			Self::Parse(err) => Some(err),
+			Self::Reload(err) => Some(err),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// Imports
+	use {super::*, tracing_subscriber::Registry};
+
+	/// Creates a filter with `directives`, along with it's layer
+	fn filter(directives: &str) -> (ReloadFilter, reload::Layer<EnvFilter, Registry>) {
+		ReloadFilter::new(EnvFilter::builder(), directives)
+	}
+
+	#[test]
+	fn new_invalid() {
+		let (filter, _layer) = filter("info,app=loud");
+		assert_eq!(filter.directives(), "info");
+		filter.add("app=debug").expect("Unable to add directives");
+		assert_eq!(filter.directives(), "info,app=debug");
+	}
+
+	#[test]
+	fn set() {
+		let (filter, _layer) = filter("info");
+		filter.set("app=debug").expect("Unable to set directives");
+		assert_eq!(filter.directives(), "app=debug");
+	}
+
+	#[test]
+	fn add() {
+		let (filter, _layer) = filter("info");
+		filter.add("app=debug").expect("Unable to add directives");
+		filter.add("app::net=trace").expect("Unable to add directives");
+		assert_eq!(filter.directives(), "info,app=debug,app::net=trace");
+	}
+
+	#[test]
+	fn add_empty() {
+		let (filter, _layer) = filter("");
+		filter.add("app=debug").expect("Unable to add directives");
+		assert_eq!(filter.directives(), "app=debug");
+	}
+
+	#[test]
+	#[cfg(feature = "config")]
+	fn set_base() {
+		let (filter, _layer) = filter("info");
+		filter.add("app=debug").expect("Unable to add directives");
+		filter.set_base("warn").expect("Unable to set base directives");
+		assert_eq!(filter.directives(), "warn,app=debug");
+
+		filter.set("error").expect("Unable to set directives");
+		filter.set_base("trace").expect("Unable to set base directives");
+		assert_eq!(filter.directives(), "error");
+	}
+
+	#[test]
+	fn invalid() {
+		let (filter, _layer) = filter("info");
+		assert!(matches!(filter.set("app=loud"), Err(FilterError::Parse(_))));
+		assert!(matches!(filter.add("app=loud"), Err(FilterError::Parse(_))));
+		#[cfg(feature = "config")]
+		assert!(matches!(filter.set_base("app=loud"), Err(FilterError::Parse(_))));
+		assert_eq!(filter.directives(), "info");
+	}
+
+	#[test]
+	fn dropped_layer() {
+		let (filter, layer) = filter("info");
+		drop(layer);
+		assert!(matches!(filter.set("debug"), Err(FilterError::Reload(_))));
+		assert_eq!(filter.directives(), "info");
+	}
+}
@@ -0,0 +1,138 @@
+//! Log formats
+
+// Imports
+use {
+	std::fmt::{self, Write},
+	tracing::{
+		Event,
+		Subscriber,
+		field::{Field, Visit},
+	},
+	tracing_subscriber::{
+		fmt::{
+			FmtContext,
+			FormatEvent,
+			FormatFields,
+			format::Writer,
+			time::{FormatTime, SystemTime},
+		},
+		registry::LookupSpan,
+	},
+};
+
+/// Format of the log file
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum FileFormat {
+	/// Human-readable text
+	#[default]
+	Text,
+
+	/// JSON lines.
+	///
+	/// Each event is a JSON object, including the current span
+	/// and the list of all entered spans, along with their fields.
+	Json,
+
+	/// Logfmt.
+	///
+	/// Each event is a line of `key=value` pairs, with the entered
+	/// spans listed under `spans`.
+	Logfmt,
+}
+
+/// Logfmt event formatter
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Logfmt;
+
+impl<S, N> FormatEvent<S, N> for Logfmt
+where
+	S: Subscriber + for<'a> LookupSpan<'a>,
+	N: for<'a> FormatFields<'a> + 'static,
+{
+	fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+		let metadata = event.metadata();
+
+		let mut time = String::new();
+		SystemTime.format_time(&mut Writer::new(&mut time))?;
+		write!(
+			writer,
+			"ts={time} level={} target=",
+			metadata.level().as_str().to_lowercase()
+		)?;
+		self::write_value(&mut writer, metadata.target())?;
+
+		if let Some(scope) = ctx.event_scope() {
+			let spans = scope.from_root().map(|span| span.name()).collect::<Vec<_>>();
+			writer.write_str(" spans=")?;
+			self::write_value(&mut writer, &spans.join(">"))?;
+		}
+
+		let mut visitor = LogfmtVisitor {
+			writer: &mut writer,
+			res:    Ok(()),
+		};
+		event.record(&mut visitor);
+		visitor.res?;
+
+		writeln!(writer)
+	}
+}
+
+/// Logfmt field visitor
+struct LogfmtVisitor<'a, 'w> {
+	/// Writer
+	writer: &'a mut Writer<'w>,
+
+	/// Result
+	res: fmt::Result,
+}
+
+impl LogfmtVisitor<'_, '_> {
+	/// Writes a field
+	fn write_field(&mut self, field: &Field, value: &str) {
+		if self.res.is_err() {
+			return;
+		}
+
+		let key = match field.name() {
+			"message" => "msg",
+			name => name,
+		};
+		self.res = write!(self.writer, " {key}=").and_then(|()| self::write_value(self.writer, value));
+	}
+}
+
+impl Visit for LogfmtVisitor<'_, '_> {
+	fn record_str(&mut self, field: &Field, value: &str) {
+		self.write_field(field, value);
+	}
+
+	fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+		self.write_field(field, &format!("{value:?}"));
+	}
+}
+
+/// Writes a logfmt value, quoting it if necessary
+fn write_value(writer: &mut impl Write, value: &str) -> fmt::Result {
+	let needs_quotes = value.is_empty() ||
+		value
+			.chars()
+			.any(|ch| ch.is_whitespace() || matches!(ch, '"' | '=' | '\\'));
+	if !needs_quotes {
+		return writer.write_str(value);
+	}
+
+	writer.write_char('"')?;
+	for ch in value.chars() {
+		match ch {
+			'"' => writer.write_str("\\\"")?,
+			'\\' => writer.write_str("\\\\")?,
+			'\n' => writer.write_str("\\n")?,
+			'\r' => writer.write_str("\\r")?,
+			'\t' => writer.write_str("\\t")?,
+			ch => writer.write_char(ch)?,
+		}
+	}
+	writer.write_char('"')
+}
@@ -0,0 +1,277 @@
+//! In-memory event history
+
+// Imports
+use {
+	std::{
+		collections::VecDeque,
+		fmt,
+		sync::{
+			Arc,
+			atomic::{self, AtomicU64},
+			mpsc,
+			nonpoison::Mutex,
+		},
+		time::SystemTime,
+	},
+	tracing::{
+		Event,
+		Level,
+		Subscriber,
+		field::{Field, Visit},
+	},
+	tracing_subscriber::{Layer, layer::Context, registry::LookupSpan},
+};
+
+/// Number of events queued for each subscriber.
+///
+/// Once a subscriber's queue is full, new events are dropped for it.
+pub const SUBSCRIBER_CAPACITY: usize = 1024;
+
+/// Log event
+#[derive(Clone, Debug)]
+pub struct LogEvent {
+	/// Time the event was emitted
+	pub timestamp: SystemTime,
+
+	/// Level
+	pub level: Level,
+
+	/// Target
+	pub target: String,
+
+	/// Message
+	pub message: String,
+
+	/// Fields, other than the message
+	pub fields: Vec<(&'static str, String)>,
+
+	/// Names of all entered spans, from the root
+	pub spans: Vec<&'static str>,
+}
+
+impl fmt::Display for LogEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:>5} {}", self.level, self.target)?;
+		for span in &self.spans {
+			write!(f, ":{span}")?;
+		}
+		write!(f, ": {}", self.message)?;
+		for (name, value) in &self.fields {
+			write!(f, " {name}={value}")?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Event history
+#[derive(Debug)]
+pub struct History {
+	/// Events
+	events: Mutex<VecDeque<Arc<LogEvent>>>,
+
+	/// Capacity
+	capacity: usize,
+
+	/// Subscribers
+	subscribers: Mutex<Vec<mpsc::SyncSender<Arc<LogEvent>>>>,
+
+	/// Number of events dropped for subscribers with a full queue
+	dropped: AtomicU64,
+}
+
+impl History {
+	/// Creates a new history, keeping the last `capacity` events
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			events: Mutex::new(VecDeque::with_capacity(capacity)),
+			capacity,
+			subscribers: Mutex::new(vec![]),
+			dropped: AtomicU64::new(0),
+		}
+	}
+
+	/// Returns all events, from oldest to newest
+	pub fn events(&self) -> Vec<Arc<LogEvent>> {
+		self.events.lock().iter().cloned().collect()
+	}
+
+	/// Subscribes to all new events.
+	///
+	/// Up to [`SUBSCRIBER_CAPACITY`] events are queued, after which
+	/// new events are dropped until the subscriber catches up.
+	pub fn subscribe(&self) -> mpsc::Receiver<Arc<LogEvent>> {
+		let (tx, rx) = mpsc::sync_channel(SUBSCRIBER_CAPACITY);
+		self.subscribers.lock().push(tx);
+		rx
+	}
+
+	/// Returns the number of events dropped for subscribers with a full queue
+	pub fn dropped(&self) -> u64 {
+		self.dropped.load(atomic::Ordering::Relaxed)
+	}
+
+	/// Returns whether anyone is interested in new events
+	fn is_active(&self) -> bool {
+		self.capacity != 0 || !self.subscribers.lock().is_empty()
+	}
+
+	/// Adds an event
+	fn push(&self, event: LogEvent) {
+		let event = Arc::new(event);
+
+		if self.capacity != 0 {
+			let mut events = self.events.lock();
+			if events.len() == self.capacity {
+				events.pop_front();
+			}
+			events.push_back(Arc::clone(&event));
+		}
+
+		// Note: Any disconnected subscribers have been dropped.
+		self.subscribers
+			.lock()
+			.retain(|subscriber| match subscriber.try_send(Arc::clone(&event)) {
+				Ok(()) => true,
+				Err(mpsc::TrySendError::Full(_)) => {
+					self.dropped.fetch_add(1, atomic::Ordering::Relaxed);
+					true
+				},
+				Err(mpsc::TrySendError::Disconnected(_)) => false,
+			});
+	}
+}
+
+/// History layer
+#[derive(Debug)]
+pub struct HistoryLayer {
+	/// History
+	history: Arc<History>,
+}
+
+impl HistoryLayer {
+	/// Creates a new layer, adding all events to `history`
+	pub const fn new(history: Arc<History>) -> Self {
+		Self { history }
+	}
+}
+
+impl<S> Layer<S> for HistoryLayer
+where
+	S: Subscriber + for<'a> LookupSpan<'a>,
+{
+	fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+		if !self.history.is_active() {
+			return;
+		}
+
+		let metadata = event.metadata();
+		let mut visitor = HistoryVisitor {
+			message: String::new(),
+			fields:  vec![],
+		};
+		event.record(&mut visitor);
+
+		let spans = ctx
+			.event_scope(event)
+			.map(|scope| scope.from_root().map(|span| span.name()).collect())
+			.unwrap_or_default();
+
+		self.history.push(LogEvent {
+			timestamp: SystemTime::now(),
+			level: *metadata.level(),
+			target: metadata.target().to_owned(),
+			message: visitor.message,
+			fields: visitor.fields,
+			spans,
+		});
+	}
+}
+
+/// History field visitor
+struct HistoryVisitor {
+	/// Message
+	message: String,
+
+	/// Fields
+	fields: Vec<(&'static str, String)>,
+}
+
+impl Visit for HistoryVisitor {
+	fn record_str(&mut self, field: &Field, value: &str) {
+		match field.name() {
+			"message" => value.clone_into(&mut self.message),
+			name => self.fields.push((name, value.to_owned())),
+		}
+	}
+
+	fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+		match field.name() {
+			"message" => self.message = format!("{value:?}"),
+			name => self.fields.push((name, format!("{value:?}"))),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// Imports
+	use super::*;
+
+	/// Creates an event with `message`
+	fn event(message: &str) -> LogEvent {
+		LogEvent {
+			timestamp: SystemTime::now(),
+			level:     Level::INFO,
+			target:    "app".to_owned(),
+			message:   message.to_owned(),
+			fields:    vec![],
+			spans:     vec![],
+		}
+	}
+
+	/// Returns the messages of `events`
+	fn messages(events: impl IntoIterator<Item = Arc<LogEvent>>) -> Vec<String> {
+		events.into_iter().map(|event| event.message.clone()).collect()
+	}
+
+	#[test]
+	fn capacity() {
+		let history = History::new(2);
+		for message in ["1", "2", "3"] {
+			history.push(event(message));
+		}
+
+		assert_eq!(messages(history.events()), ["2", "3"]);
+	}
+
+	#[test]
+	fn subscribe() {
+		let history = History::new(0);
+		let rx = history.subscribe();
+		history.push(event("1"));
+		history.push(event("2"));
+
+		assert_eq!(messages(rx.try_iter()), ["1", "2"]);
+		assert!(history.events().is_empty());
+
+		drop(rx);
+		history.push(event("3"));
+		assert!(!history.is_active());
+	}
+
+	#[test]
+	fn slow_subscriber() {
+		let history = History::new(0);
+		let rx = history.subscribe();
+		for _ in 0..SUBSCRIBER_CAPACITY + 5 {
+			history.push(event("event"));
+		}
+
+		assert_eq!(rx.try_iter().count(), SUBSCRIBER_CAPACITY);
+		assert_eq!(history.dropped(), 5);
+
+		history.push(event("event"));
+		assert_eq!(rx.try_iter().count(), 1);
+	}
+}
@@ -9,24 +9,54 @@
 )]
 
 // Modules
+mod archive;
 mod builder;
+#[cfg(feature = "config")]
+mod config;
 mod file;
+mod filter;
+mod format;
+mod history;
+mod memory;
+mod non_blocking;
+mod panic;
 mod pre_init;
+mod rotate;
+mod sink;
 mod term;
 
 // Exports
-pub use self::builder::LoggerBuilder;
+#[cfg(feature = "config")]
+pub use self::config::{
+	ConfigError,
+	FileConfig,
+	LoggerConfig,
+	NonBlockingConfig,
+	RotationConfig,
+	SinkConfig,
+	StderrConfig,
+};
+pub use self::{
+	builder::LoggerBuilder,
+	filter::FilterError,
+	format::{FileFormat, Logfmt},
+	history::LogEvent,
+	non_blocking::OverflowPolicy,
+	panic::PanicHook,
+	rotate::{Rotation, RotationPeriod},
+	sink::{FileSink, Sink},
+};
 
 // Imports
 use {
-	self::file::FileWriter,
+	self::{file::FileWriter, filter::ReloadFilter, history::History},
 	itertools::Itertools,
 	std::{
 		collections::{HashMap, hash_map},
 		env::{self, VarError},
-		fs,
 		io,
 		path::Path,
+		sync::{Arc, mpsc},
 	},
 	tracing_subscriber::Registry,
 };
@@ -35,6 +65,22 @@ use {
 pub struct Logger {
 	/// File writer
 	file_writer: FileWriter,
+
+	/// Stderr filter
+	stderr_filter: Arc<ReloadFilter>,
+
+	/// File filter
+	file_filter: Arc<ReloadFilter>,
+
+	/// Event history
+	history: Arc<History>,
+
+	/// File sinks
+	sinks: HashMap<String, Sink>,
+
+	/// Configuration watcher
+	#[cfg(feature = "config")]
+	_config_watcher: Option<config::ConfigWatcher>,
 }
 
 impl Logger {
@@ -53,14 +99,14 @@ impl Logger {
 	/// Sets a file to log into.
 	///
 	/// Once the logger is finished, any logs produced until then
-	/// will be retro-actively written into this log file.
+	/// will be retro-actively written into this log file, up to the
+	/// limit set by [`LoggerBuilder::file_buffer_limit`].
+	///
+	/// The file is rotated according to [`LoggerBuilder::file_rotation`].
 	pub fn set_file(&self, path: Option<&Path>) {
 		match path {
-			Some(path) => match fs::File::create(path) {
-				Ok(file) => {
-					self.file_writer.set_file(file);
-					tracing::info!("Logging to file: {path:?}");
-				},
+			Some(path) => match self.file_writer.set_file(path) {
+				Ok(()) => tracing::info!("Logging to file: {path:?}"),
 				Err(err) => {
 					tracing::warn!("Unable to create log file {path:?}: {err}");
 					self.file_writer.set_empty();
@@ -69,6 +115,110 @@ impl Logger {
 			None => self.file_writer.set_empty(),
 		}
 	}
+
+	/// Returns a file sink, by name.
+	///
+	/// See [`LoggerBuilder::file_sink`].
+	#[must_use]
+	pub fn sink(&self, name: &str) -> Option<&Sink> {
+		self.sinks.get(name)
+	}
+
+	/// Returns all file sinks
+	pub fn sinks(&self) -> impl Iterator<Item = &Sink> {
+		self.sinks.values()
+	}
+
+	/// Flushes the log file and all sinks.
+	///
+	/// If non-blocking, waits until all queued events are written.
+	pub fn flush(&self) {
+		if let Err(err) = self.file_writer.flush() {
+			tracing::warn!("Unable to flush log file: {err}");
+		}
+		for sink in self.sinks.values() {
+			sink.flush();
+		}
+	}
+
+	/// Returns the most recent events, from oldest to newest.
+	///
+	/// Keeps up to [`LoggerBuilder::history_capacity`] events.
+	#[must_use]
+	pub fn recent_events(&self) -> Vec<Arc<LogEvent>> {
+		self.history.events()
+	}
+
+	/// Subscribes to all new events.
+	///
+	/// Events are filtered by [`LoggerBuilder::history_filter`].
+	/// Dropping the receiver unsubscribes.
+	///
+	/// Up to 1024 events are queued for the receiver. If it falls behind,
+	/// new events are dropped for it until it catches up, see
+	/// [`Self::dropped_subscriber_events`].
+	#[must_use]
+	pub fn subscribe_events(&self) -> mpsc::Receiver<Arc<LogEvent>> {
+		self.history.subscribe()
+	}
+
+	/// Returns the number of events dropped for subscribers that fell behind.
+	///
+	/// See [`Self::subscribe_events`].
+	#[must_use]
+	pub fn dropped_subscriber_events(&self) -> u64 {
+		self.history.dropped()
+	}
+
+	/// Returns the current stderr filter directives
+	#[must_use]
+	pub fn stderr_filter(&self) -> String {
+		self.stderr_filter.directives()
+	}
+
+	/// Replaces the stderr filter directives.
+	///
+	/// These are kept across reloads of a watched configuration file,
+	/// replacing it's directives.
+	pub fn set_stderr_filter(&self, directives: &str) -> Result<(), FilterError> {
+		self.stderr_filter.set(directives)
+	}
+
+	/// Adds stderr filter directives.
+	///
+	/// Overrides any existing directives for the same targets.
+	/// These are kept across reloads of a watched configuration file.
+	pub fn add_stderr_filter(&self, directives: &str) -> Result<(), FilterError> {
+		self.stderr_filter.add(directives)
+	}
+
+	/// Returns the current file filter directives
+	#[must_use]
+	pub fn file_filter(&self) -> String {
+		self.file_filter.directives()
+	}
+
+	/// Replaces the file filter directives.
+	///
+	/// These are kept across reloads of a watched configuration file,
+	/// replacing it's directives.
+	pub fn set_file_filter(&self, directives: &str) -> Result<(), FilterError> {
+		self.file_filter.set(directives)
+	}
+
+	/// Adds file filter directives.
+	///
+	/// Overrides any existing directives for the same targets.
+	/// These are kept across reloads of a watched configuration file.
+	pub fn add_file_filter(&self, directives: &str) -> Result<(), FilterError> {
+		self.file_filter.add(directives)
+	}
+}
+
+impl Drop for Logger {
+	fn drop(&mut self) {
+		self.flush();
+	}
 }
 
 impl Default for Logger {
@@ -130,3 +280,12 @@ fn get_env_filters(env: &str, default_filters: impl IntoIterator<Item = (Option<
 
 	var
 }
+
+/// Creates an empty temporary directory for a test
+#[cfg(test)]
+fn temp_dir(name: &str) -> std::path::PathBuf {
+	let path = env::temp_dir().join(format!("zutil-logger-{}-{name}", std::process::id()));
+	let _ = std::fs::remove_dir_all(&path);
+	std::fs::create_dir_all(&path).expect("Unable to create temporary directory");
+	path
+}
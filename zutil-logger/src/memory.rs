@@ -0,0 +1,127 @@
+//! In-memory log buffer
+
+// Imports
+use std::{
+	collections::VecDeque,
+	io::{self, Write},
+};
+
+/// Default limit of the buffer, in bytes
+pub const DEFAULT_LIMIT: usize = 16 * 1024 * 1024;
+
+/// In-memory log buffer.
+///
+/// Keeps at most `limit` bytes, dropping the oldest lines
+/// once full.
+#[derive(Debug)]
+pub struct MemoryBuffer {
+	/// Bytes
+	bytes: VecDeque<u8>,
+
+	/// Limit, in bytes
+	limit: usize,
+
+	/// Number of dropped lines
+	dropped_lines: usize,
+}
+
+impl MemoryBuffer {
+	/// Creates a new, empty, buffer
+	pub const fn new(limit: usize) -> Self {
+		Self {
+			bytes: VecDeque::new(),
+			limit,
+			dropped_lines: 0,
+		}
+	}
+
+	/// Writes all buffered data into `writer`.
+	///
+	/// If any lines were dropped, they're replaced by a marker line.
+	pub fn write_into(&self, writer: &mut impl Write) -> io::Result<()> {
+		if self.dropped_lines != 0 {
+			writeln!(
+				writer,
+				"[{} earlier log line(s) were dropped after exceeding the {} byte buffer]",
+				self.dropped_lines, self.limit
+			)?;
+		}
+
+		let (front, back) = self.bytes.as_slices();
+		writer.write_all(front)?;
+		writer.write_all(back)
+	}
+
+	/// Drops the oldest lines until we're within the limit
+	fn drop_excess(&mut self) {
+		while self.bytes.len() > self.limit {
+			// Note: If there's no line end, we're in the middle of a line
+			//       that's larger than the limit, so drop all of it.
+			let len = match self.bytes.iter().position(|&b| b == b'\n') {
+				Some(pos) => pos + 1,
+				None => self.bytes.len(),
+			};
+			self.bytes.drain(..len);
+			self.dropped_lines += 1;
+		}
+	}
+}
+
+impl io::Write for MemoryBuffer {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.bytes.extend(buf);
+		self.drop_excess();
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// Imports
+	use super::*;
+
+	/// Writes `lines` into a buffer with `limit` and returns it's output
+	fn output(limit: usize, lines: &[&str]) -> String {
+		let mut buffer = MemoryBuffer::new(limit);
+		for line in lines {
+			buffer.write_all(line.as_bytes()).expect("Unable to write");
+		}
+
+		let mut output = vec![];
+		buffer.write_into(&mut output).expect("Unable to write output");
+		String::from_utf8(output).expect("Output wasn't utf-8")
+	}
+
+	#[test]
+	fn within_limit() {
+		assert_eq!(output(13, &["first\n", "second\n"]), "first\nsecond\n");
+	}
+
+	#[test]
+	fn drop_oldest() {
+		assert_eq!(
+			output(14, &["first\n", "second\n", "third\n"]),
+			"[1 earlier log line(s) were dropped after exceeding the 14 byte buffer]\nsecond\nthird\n"
+		);
+	}
+
+	#[test]
+	fn drop_oversized() {
+		assert_eq!(
+			output(4, &["first\n", "second"]),
+			"[2 earlier log line(s) were dropped after exceeding the 4 byte buffer]\n"
+		);
+	}
+
+	#[test]
+	fn partial_writes() {
+		assert_eq!(
+			output(8, &["fir", "st\nsec", "ond\n"]),
+			"[1 earlier log line(s) were dropped after exceeding the 8 byte buffer]\nsecond\n"
+		);
+	}
+}
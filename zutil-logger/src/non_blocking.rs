@@ -0,0 +1,299 @@
+//! Non-blocking writing
+
+// Imports
+use {
+	std::{
+		cell::Cell,
+		io::{self, Write},
+		sync::{
+			atomic::{self, AtomicU64},
+			mpsc,
+		},
+		thread,
+		time::{Duration, Instant},
+	},
+	tracing_subscriber::fmt::MakeWriter,
+};
+
+/// Policy for when the queue is full
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum OverflowPolicy {
+	/// Blocks until there's space in the queue
+	#[default]
+	Block,
+
+	/// Drops the event.
+	///
+	/// The number of dropped events is written once there's space again.
+	Drop,
+}
+
+thread_local! {
+	/// Whether this thread is a log writer thread
+	static IS_WRITER_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Returns whether the current thread is a log writer thread
+pub fn is_writer_thread() -> bool {
+	IS_WRITER_THREAD.get()
+}
+
+/// Queue message
+enum Message {
+	/// Write bytes
+	Write(Vec<u8>),
+
+	/// Flush, then notify
+	Flush(mpsc::SyncSender<()>),
+}
+
+/// Write queue.
+///
+/// Queues all writes to be performed by a background thread.
+#[derive(Debug)]
+pub struct Queue {
+	/// Sender
+	tx: mpsc::SyncSender<Message>,
+
+	/// Policy
+	policy: OverflowPolicy,
+
+	/// Number of dropped writes since the last write
+	dropped: AtomicU64,
+}
+
+impl Queue {
+	/// Spawns the queue, of `capacity` writes, writing into `writer`
+	pub fn spawn<W>(writer: W, capacity: usize, policy: OverflowPolicy) -> io::Result<Self>
+	where
+		W: for<'a> MakeWriter<'a> + Send + 'static,
+	{
+		let (tx, rx) = mpsc::sync_channel(capacity);
+		let queue = Self {
+			tx,
+			policy,
+			dropped: AtomicU64::new(0),
+		};
+
+		thread::Builder::new().name("log-writer".to_owned()).spawn(move || {
+			IS_WRITER_THREAD.set(true);
+
+			// Note: We can't log here, since we're within the logger, so
+			//       we just report to stderr.
+			for msg in rx {
+				match msg {
+					Message::Write(bytes) =>
+						if let Err(err) = writer.make_writer().write_all(&bytes) {
+							eprintln!("Unable to write log: {err}");
+						},
+					Message::Flush(done_tx) => {
+						if let Err(err) = writer.make_writer().flush() {
+							eprintln!("Unable to flush log: {err}");
+						}
+						let _ = done_tx.send(());
+					},
+				}
+			}
+		})?;
+
+		Ok(queue)
+	}
+
+	/// Queues a write
+	pub fn write(&self, bytes: &[u8]) {
+		let mut bytes = bytes.to_vec();
+
+		// If we dropped anything, let the reader know before this write
+		let dropped = self.dropped.swap(0, atomic::Ordering::AcqRel);
+		if dropped != 0 {
+			let marker = format!("[{dropped} log event(s) were dropped due to a full queue]\n");
+			bytes.splice(..0, marker.into_bytes());
+		}
+
+		let res = match self.policy {
+			OverflowPolicy::Block => self.tx.send(Message::Write(bytes)).map_err(|_| ()),
+			OverflowPolicy::Drop => match self.tx.try_send(Message::Write(bytes)) {
+				Ok(()) => Ok(()),
+				Err(mpsc::TrySendError::Full(_)) => {
+					self.dropped.fetch_add(dropped + 1, atomic::Ordering::AcqRel);
+					Ok(())
+				},
+				Err(mpsc::TrySendError::Disconnected(_)) => Err(()),
+			},
+		};
+
+		if res.is_err() {
+			eprintln!("Log writer stopped, dropping log");
+		}
+	}
+
+	/// Waits until all queued writes are written and flushed
+	pub fn flush(&self) {
+		let (done_tx, done_rx) = mpsc::sync_channel(1);
+		if self.tx.send(Message::Flush(done_tx)).is_err() || done_rx.recv().is_err() {
+			eprintln!("Log writer stopped, unable to flush");
+		}
+	}
+
+	/// Waits until all queued writes are written and flushed, for at most `timeout`
+	pub fn flush_timeout(&self, timeout: Duration) -> io::Result<()> {
+		let deadline = Instant::now() + timeout;
+		let timed_out = || io::Error::new(io::ErrorKind::TimedOut, "Timed out waiting for the log writer");
+		let stopped = || io::Error::other("Log writer stopped");
+
+		let (done_tx, done_rx) = mpsc::sync_channel(1);
+		let mut msg = Message::Flush(done_tx);
+		loop {
+			match self.tx.try_send(msg) {
+				Ok(()) => break,
+				Err(mpsc::TrySendError::Full(_)) if Instant::now() >= deadline => return Err(timed_out()),
+				Err(mpsc::TrySendError::Full(full_msg)) => {
+					msg = full_msg;
+					thread::sleep(Duration::from_millis(1));
+				},
+				Err(mpsc::TrySendError::Disconnected(_)) => return Err(stopped()),
+			}
+		}
+
+		done_rx
+			.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+			.map_err(|err| match err {
+				mpsc::RecvTimeoutError::Timeout => timed_out(),
+				mpsc::RecvTimeoutError::Disconnected => stopped(),
+			})
+	}
+}
+
+/// Writer for a queue
+#[derive(Debug)]
+pub struct QueueWriter<'a>(pub &'a Queue);
+
+impl io::Write for QueueWriter<'_> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.write(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// Imports
+	use {
+		super::*,
+		std::sync::{Arc, Mutex},
+	};
+
+	/// Writer that waits for a gate before writing
+	#[derive(Clone)]
+	struct GatedWriter {
+		/// Output
+		output: Arc<Mutex<Vec<u8>>>,
+
+		/// Gate, opened once it's sender is dropped
+		gate: Arc<Mutex<mpsc::Receiver<()>>>,
+
+		/// Notified whenever a write starts
+		started_tx: mpsc::Sender<()>,
+	}
+
+	impl io::Write for GatedWriter {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			let _ = self.started_tx.send(());
+			let _ = self.gate.lock().expect("Poisoned").recv();
+			self.output.lock().expect("Poisoned").write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	impl MakeWriter<'_> for GatedWriter {
+		type Writer = Self;
+
+		fn make_writer(&self) -> Self::Writer {
+			self.clone()
+		}
+	}
+
+	/// Spawns a queue with a capacity of 1, with it's writer blocked on a write.
+	///
+	/// Returns the queue, the output and the gate, which unblocks the writer once dropped.
+	fn blocked_queue(policy: OverflowPolicy) -> (Queue, Arc<Mutex<Vec<u8>>>, mpsc::Sender<()>) {
+		let output = Arc::new(Mutex::new(vec![]));
+		let (gate_tx, gate_rx) = mpsc::channel();
+		let (started_tx, started_rx) = mpsc::channel();
+		let writer = GatedWriter {
+			output: Arc::clone(&output),
+			gate: Arc::new(Mutex::new(gate_rx)),
+			started_tx,
+		};
+
+		let queue = Queue::spawn(writer, 1, policy).expect("Unable to spawn queue");
+		queue.write(b"a\n");
+		started_rx.recv().expect("Writer should have started");
+
+		(queue, output, gate_tx)
+	}
+
+	/// Returns the output as a string
+	fn output(output: &Mutex<Vec<u8>>) -> String {
+		String::from_utf8(output.lock().expect("Poisoned").clone()).expect("Output wasn't utf-8")
+	}
+
+	#[test]
+	fn drop_policy() {
+		let (queue, output, gate) = blocked_queue(OverflowPolicy::Drop);
+		queue.write(b"b\n");
+		queue.write(b"c\n");
+		queue.write(b"d\n");
+
+		drop(gate);
+		queue.flush();
+		queue.write(b"e\n");
+		queue.flush();
+		assert_eq!(
+			self::output(&output),
+			"a\nb\n[2 log event(s) were dropped due to a full queue]\ne\n"
+		);
+	}
+
+	#[test]
+	fn block_policy() {
+		let (queue, output, gate) = blocked_queue(OverflowPolicy::Block);
+		let queue = Arc::new(queue);
+		queue.write(b"b\n");
+
+		let writer = thread::spawn({
+			let queue = Arc::clone(&queue);
+			move || queue.write(b"c\n")
+		});
+		thread::sleep(Duration::from_millis(50));
+		assert!(!writer.is_finished());
+
+		drop(gate);
+		writer.join().expect("Writer panicked");
+		queue.flush();
+		assert_eq!(self::output(&output), "a\nb\nc\n");
+	}
+
+	#[test]
+	fn flush_timeout() {
+		let (queue, output, gate) = blocked_queue(OverflowPolicy::Block);
+		queue.write(b"b\n");
+
+		let err = queue
+			.flush_timeout(Duration::from_millis(10))
+			.expect_err("Flush should time out");
+		assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+		drop(gate);
+		queue.flush_timeout(Duration::from_secs(5)).expect("Unable to flush");
+		assert_eq!(self::output(&output), "a\nb\n");
+	}
+}
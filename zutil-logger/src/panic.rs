@@ -0,0 +1,136 @@
+//! Panic hook
+
+// Imports
+use {
+	crate::{file::FileWriter, non_blocking},
+	std::{
+		backtrace::Backtrace,
+		cell::Cell,
+		fmt::Write,
+		fs,
+		panic::{self, PanicHookInfo},
+		path::{Path, PathBuf},
+		thread,
+		time::Duration,
+	},
+	tracing_subscriber::fmt::{
+		format::Writer,
+		time::{FormatTime, SystemTime},
+	},
+};
+
+/// Time to wait for each log file to be flushed after a panic
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+thread_local! {
+	/// Whether this thread is within the panic hook
+	static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Panic hook.
+///
+/// Wraps the current panic hook with one that logs panics as an
+/// `error` event, with the thread name, location and a backtrace,
+/// and then flushes the log file and all sinks, before calling the
+/// previous hook.
+///
+/// Panics within the hook itself, or within the non-blocking log writer,
+/// aren't logged, since that could hang, but are still reported by the
+/// previous hook.
+#[derive(Clone, Default, Debug)]
+pub struct PanicHook {
+	/// Crash report path
+	crash_report: Option<PathBuf>,
+}
+
+impl PanicHook {
+	/// Creates a new panic hook, without a crash report
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { crash_report: None }
+	}
+
+	/// Additionally writes a crash report to `path` on panic.
+	///
+	/// Any existing file is overwritten.
+	#[must_use]
+	pub fn crash_report(self, path: &Path) -> Self {
+		Self {
+			crash_report: Some(path.to_owned()),
+		}
+	}
+
+	/// Installs this hook, flushing `writers` after each panic
+	pub(crate) fn install(self, writers: Vec<FileWriter>) {
+		let prev_hook = panic::take_hook();
+		panic::set_hook(Box::new(move |info| {
+			let was_in_hook = IN_HOOK.replace(true);
+			if !was_in_hook {
+				self.report(info, &writers);
+			}
+			IN_HOOK.set(was_in_hook);
+
+			prev_hook(info);
+		}));
+	}
+
+	/// Reports a panic
+	fn report(&self, info: &PanicHookInfo<'_>, writers: &[FileWriter]) {
+		let thread = thread::current();
+		let thread_name = thread.name().unwrap_or("<unnamed>");
+		let location = info
+			.location()
+			.map_or_else(|| "<unknown>".to_owned(), ToString::to_string);
+		let payload = self::payload(info);
+		let backtrace = Backtrace::force_capture();
+
+		// Note: We write the crash report before logging, in case the
+		//       panic happened within the logger and logging hangs.
+		if let Some(path) = &self.crash_report {
+			let report = self::crash_report(thread_name, &location, payload, &backtrace);
+			if let Err(err) = fs::write(path, report) {
+				eprintln!("Unable to write crash report {}: {err}", path.display());
+			}
+		}
+
+		// Note: If the log writer panicked, it can't write anything, and any
+		//       logging or flushing would wait for it forever.
+		if non_blocking::is_writer_thread() {
+			return;
+		}
+
+		tracing::error!(
+			thread = thread_name,
+			location,
+			%backtrace,
+			"Thread {thread_name:?} panicked at {location}: {payload}"
+		);
+
+		for writer in writers {
+			if let Err(err) = writer.flush_timeout(FLUSH_TIMEOUT) {
+				eprintln!("Unable to flush log file: {err}");
+			}
+		}
+	}
+}
+
+/// Returns the payload of a panic as a string
+fn payload<'a>(info: &'a PanicHookInfo<'_>) -> &'a str {
+	info.payload_as_str().unwrap_or("Box<dyn Any>")
+}
+
+/// Creates a crash report
+fn crash_report(thread_name: &str, location: &str, payload: &str, backtrace: &Backtrace) -> String {
+	// Note: Writing to a string can't fail
+	let mut time = String::new();
+	let _ = SystemTime.format_time(&mut Writer::new(&mut time));
+
+	let mut report = String::new();
+	let _ = writeln!(report, "Time: {time}");
+	let _ = writeln!(report, "Thread: {thread_name}");
+	let _ = writeln!(report, "Location: {location}");
+	let _ = writeln!(report, "Message: {payload}");
+	let _ = writeln!(report, "Backtrace:\n{backtrace}");
+
+	report
+}
@@ -0,0 +1,410 @@
+//! Log file rotation
+
+// Imports
+use {
+	crate::archive::Archiver,
+	std::{
+		fs,
+		io::{self, Write},
+		path::{Path, PathBuf},
+		process,
+		time::{Duration, SystemTime, UNIX_EPOCH},
+	},
+};
+
+/// Log file rotation.
+///
+/// Rotated files are named after the log file, with an index appended,
+/// where `1` is the most recent. For example, `app.log` would be rotated
+/// into `app.log.1`, `app.log.2`, and so on. Compressed files have an
+/// additional `.gz` extension.
+///
+/// Rotated files are managed by a background thread.
+#[derive(Clone, Debug)]
+pub struct Rotation {
+	/// Maximum size of a log file, in bytes
+	max_size: Option<u64>,
+
+	/// Period
+	period: Option<RotationPeriod>,
+
+	/// Number of rotated files to keep
+	pub(crate) keep: usize,
+
+	/// Whether to compress rotated files
+	pub(crate) compress: bool,
+
+	/// Maximum age of rotated files
+	pub(crate) max_age: Option<Duration>,
+
+	/// Maximum total size of rotated files, in bytes
+	pub(crate) max_total_size: Option<u64>,
+}
+
+impl Rotation {
+	/// Creates a new rotation, that never rotates, and keeps 5 rotated files
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			max_size:       None,
+			period:         None,
+			keep:           5,
+			compress:       false,
+			max_age:        None,
+			max_total_size: None,
+		}
+	}
+
+	/// Rotates the log file once it would exceed `max_size` bytes
+	#[must_use]
+	pub const fn max_size(self, max_size: u64) -> Self {
+		Self {
+			max_size: Some(max_size),
+			..self
+		}
+	}
+
+	/// Rotates the log file at the start of each period
+	#[must_use]
+	pub const fn period(self, period: RotationPeriod) -> Self {
+		Self {
+			period: Some(period),
+			..self
+		}
+	}
+
+	/// Sets the number of rotated files to keep
+	#[must_use]
+	pub const fn keep(self, keep: usize) -> Self {
+		Self { keep, ..self }
+	}
+
+	/// Compresses rotated files with gzip
+	#[must_use]
+	pub const fn compress(self, compress: bool) -> Self {
+		Self { compress, ..self }
+	}
+
+	/// Removes rotated files once they're older than `max_age`
+	#[must_use]
+	pub const fn max_age(self, max_age: Duration) -> Self {
+		Self {
+			max_age: Some(max_age),
+			..self
+		}
+	}
+
+	/// Removes the oldest rotated files once they exceed `max_total_size` bytes in total
+	#[must_use]
+	pub const fn max_total_size(self, max_total_size: u64) -> Self {
+		Self {
+			max_total_size: Some(max_total_size),
+			..self
+		}
+	}
+}
+
+impl Default for Rotation {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Rotation period.
+///
+/// Periods start at UTC boundaries.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum RotationPeriod {
+	/// Hourly
+	Hourly,
+
+	/// Daily
+	Daily,
+}
+
+impl RotationPeriod {
+	/// Returns the length of this period, in seconds
+	const fn secs(self) -> u64 {
+		match self {
+			Self::Hourly => 60 * 60,
+			Self::Daily => 24 * 60 * 60,
+		}
+	}
+
+	/// Returns the index of the period `time` is in
+	fn idx(self, time: SystemTime) -> u64 {
+		let secs = time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+		secs / self.secs()
+	}
+}
+
+/// Log file
+#[derive(Debug)]
+pub struct LogFile {
+	/// Path
+	path: PathBuf,
+
+	/// File
+	file: fs::File,
+
+	/// Bytes written to the file
+	size: u64,
+
+	/// Instant the file was opened
+	opened_at: SystemTime,
+
+	/// Rotation
+	rotation: Option<Rotation>,
+
+	/// Archiver, once spawned
+	archiver: Option<Archiver>,
+
+	/// Number of rotations
+	rotations: u64,
+}
+
+impl LogFile {
+	/// Creates a new log file.
+	///
+	/// If rotating, any existing, non-empty, file at `path` is rotated,
+	/// instead of being truncated. Any staged files left over from a previous
+	/// run (e.g. if it crashed before archiving them) are archived before it.
+	pub fn create(path: &Path, rotation: Option<Rotation>) -> io::Result<Self> {
+		let mut archiver = None;
+		let mut rotations = 0;
+		if let Some(rotation) = &rotation {
+			let mut staged = self::leftover_staged_paths(path)?;
+			if fs::metadata(path).is_ok_and(|metadata| metadata.len() != 0) {
+				let staged_path = self::staged_path(path, rotations);
+				fs::rename(path, &staged_path)?;
+				staged.push(staged_path);
+				rotations += 1;
+			}
+
+			if !staged.is_empty() {
+				let archiver = archiver.insert(Archiver::spawn(path.to_owned(), rotation.clone())?);
+				for staged in staged {
+					archiver.archive(staged);
+				}
+			}
+		}
+
+		let file = fs::File::create(path)?;
+		Ok(Self {
+			path: path.to_owned(),
+			file,
+			size: 0,
+			opened_at: SystemTime::now(),
+			rotation,
+			archiver,
+			rotations,
+		})
+	}
+
+	/// Returns whether we should rotate before writing `len` bytes
+	fn should_rotate(&self, len: usize) -> bool {
+		let Some(rotation) = &self.rotation else {
+			return false;
+		};
+
+		let exceeds_size = rotation
+			.max_size
+			.is_some_and(|max_size| self.size != 0 && self.size + len as u64 > max_size);
+		let exceeds_period = rotation
+			.period
+			.is_some_and(|period| period.idx(self.opened_at) != period.idx(SystemTime::now()));
+
+		exceeds_size || exceeds_period
+	}
+
+	/// Rotates the file.
+	///
+	/// The current file is staged and handed to the archiver.
+	fn rotate(&mut self) -> io::Result<()> {
+		self.file.flush()?;
+
+		let archiver = match &mut self.archiver {
+			Some(archiver) => archiver,
+			None => {
+				let rotation = self.rotation.clone().unwrap_or_default();
+				self.archiver.insert(Archiver::spawn(self.path.clone(), rotation)?)
+			},
+		};
+
+		let staged = self::staged_path(&self.path, self.rotations);
+		fs::rename(&self.path, &staged)?;
+		let file = match fs::File::create(&self.path) {
+			Ok(file) => file,
+			Err(err) => {
+				// Note: We move the file back, so we keep writing to it.
+				if let Err(err) = fs::rename(&staged, &self.path) {
+					eprintln!("Unable to restore log file {}: {err}", self.path.display());
+				}
+				return Err(err);
+			},
+		};
+		archiver.archive(staged);
+		self.rotations += 1;
+
+		self.file = file;
+		self.size = 0;
+		self.opened_at = SystemTime::now();
+
+		Ok(())
+	}
+}
+
+impl io::Write for LogFile {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		// Note: We can't log here, since we're within the logger, so
+		//       we just report to stderr and keep using the current file.
+		if self.should_rotate(buf.len()) &&
+			let Err(err) = self.rotate()
+		{
+			eprintln!("Unable to rotate log file {}: {err}", self.path.display());
+		}
+
+		let len = self.file.write(buf)?;
+		self.size += len as u64;
+		Ok(len)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.file.flush()
+	}
+}
+
+/// Returns the path to stage the `idx`th rotation of `path` at.
+///
+/// The path includes our process id, and skips over any existing files, so
+/// that we never overwrite a staged file the archiver hasn't gotten to yet.
+fn staged_path(path: &Path, mut idx: u64) -> PathBuf {
+	loop {
+		let mut staged = path.to_owned().into_os_string();
+		staged.push(format!(".pending-{}-{idx}", process::id()));
+		let staged = PathBuf::from(staged);
+		if !staged.exists() {
+			break staged;
+		}
+		idx += 1;
+	}
+}
+
+/// Returns all staged files of `path` left over from a previous run, oldest first
+fn leftover_staged_paths(path: &Path) -> io::Result<Vec<PathBuf>> {
+	let Some(file_name) = path.file_name() else {
+		return Ok(vec![]);
+	};
+	let mut prefix = file_name.to_owned();
+	prefix.push(".pending-");
+
+	let dir = match path.parent() {
+		Some(parent) if !parent.as_os_str().is_empty() => parent,
+		_ => Path::new("."),
+	};
+	let mut staged = vec![];
+	for entry in fs::read_dir(dir)? {
+		let entry = entry?;
+		if !entry
+			.file_name()
+			.as_encoded_bytes()
+			.starts_with(prefix.as_encoded_bytes())
+		{
+			continue;
+		}
+
+		let modified = entry
+			.metadata()
+			.and_then(|metadata| metadata.modified())
+			.unwrap_or(UNIX_EPOCH);
+		staged.push((modified, entry.path()));
+	}
+	staged.sort();
+
+	Ok(staged.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	// Imports
+	use {
+		super::*,
+		std::{thread, time::Instant},
+	};
+
+	/// Waits until the file at `path` exists, and returns it's contents
+	fn wait_read(path: &Path) -> String {
+		let start = Instant::now();
+		loop {
+			match fs::read_to_string(path) {
+				Ok(contents) => break contents,
+				Err(err) if err.kind() == io::ErrorKind::NotFound && start.elapsed() < Duration::from_secs(5) =>
+					thread::sleep(Duration::from_millis(10)),
+				Err(err) => panic!("Unable to read {}: {err}", path.display()),
+			}
+		}
+	}
+
+	#[test]
+	fn rotate_on_size() {
+		let dir = crate::temp_dir("rotate_on_size");
+		let path = dir.join("app.log");
+
+		let mut file = LogFile::create(&path, Some(Rotation::new().max_size(8))).expect("Unable to create log file");
+		file.write_all(b"first\n").expect("Unable to write");
+		file.write_all(b"second\n").expect("Unable to write");
+		file.flush().expect("Unable to flush");
+
+		assert_eq!(fs::read_to_string(&path).expect("Unable to read"), "second\n");
+		assert_eq!(wait_read(&dir.join("app.log.1")), "first\n");
+	}
+
+	#[test]
+	fn rotate_existing() {
+		let dir = crate::temp_dir("rotate_existing");
+		let path = dir.join("app.log");
+		fs::write(&path, "previous\n").expect("Unable to write");
+
+		let file = LogFile::create(&path, Some(Rotation::new())).expect("Unable to create log file");
+		assert_eq!(file.rotations, 1);
+		assert_eq!(fs::read_to_string(&path).expect("Unable to read"), "");
+		assert_eq!(wait_read(&dir.join("app.log.1")), "previous\n");
+	}
+
+	#[test]
+	fn recover_leftover_staged() {
+		let dir = crate::temp_dir("recover_leftover_staged");
+		let path = dir.join("app.log");
+		fs::write(dir.join("app.log.pending-0"), "crashed\n").expect("Unable to write");
+		fs::write(&path, "previous\n").expect("Unable to write");
+
+		let _file = LogFile::create(&path, Some(Rotation::new())).expect("Unable to create log file");
+		assert_eq!(wait_read(&dir.join("app.log.2")), "crashed\n");
+		assert_eq!(wait_read(&dir.join("app.log.1")), "previous\n");
+		assert!(!dir.join("app.log.pending-0").exists());
+	}
+
+	#[test]
+	fn staged_path_skips_existing() {
+		let dir = crate::temp_dir("staged_path_skips_existing");
+		let path = dir.join("app.log");
+
+		let staged = self::staged_path(&path, 0);
+		fs::write(&staged, "staged\n").expect("Unable to write");
+		let next_staged = self::staged_path(&path, 0);
+		assert_ne!(staged, next_staged);
+		assert!(!next_staged.exists());
+	}
+
+	#[test]
+	fn truncate_existing_without_rotation() {
+		let dir = crate::temp_dir("truncate_existing_without_rotation");
+		let path = dir.join("app.log");
+		fs::write(&path, "previous\n").expect("Unable to write");
+
+		let _file = LogFile::create(&path, None).expect("Unable to create log file");
+		assert_eq!(fs::read_to_string(&path).expect("Unable to read"), "");
+		assert!(!dir.join("app.log.1").exists());
+	}
+}
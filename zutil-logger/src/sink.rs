@@ -0,0 +1,186 @@
+//! Named file sinks
+
+// Imports
+use {
+	crate::{
+		file::FileWriter,
+		filter::{FilterError, ReloadFilter},
+		format::FileFormat,
+		non_blocking::OverflowPolicy,
+		rotate::Rotation,
+	},
+	std::{
+		path::{Path, PathBuf},
+		sync::Arc,
+	},
+};
+
+/// File sink.
+///
+/// An additional log file, with it's own filter, format, rotation and
+/// non-blocking writing, independent from the main log file.
+#[derive(Clone, Debug)]
+pub struct FileSink {
+	/// Filter directives
+	pub(crate) filter: String,
+
+	/// Format
+	pub(crate) format: FileFormat,
+
+	/// Path
+	pub(crate) path: Option<PathBuf>,
+
+	/// Rotation
+	pub(crate) rotation: Option<Rotation>,
+
+	/// Queue capacity and policy, if non-blocking
+	pub(crate) non_blocking: Option<(usize, OverflowPolicy)>,
+}
+
+impl FileSink {
+	/// Creates a new sink, logging at `debug` and above, in text.
+	///
+	/// Until a file is set, logs are kept in memory. Writes block, unless
+	/// [`Self::non_blocking`] is used.
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			filter:       "debug".to_owned(),
+			format:       FileFormat::Text,
+			path:         None,
+			rotation:     None,
+			non_blocking: None,
+		}
+	}
+
+	/// Sets the filter directives.
+	///
+	/// Unlike the main log file, these aren't affected by `RUST_FILE_LOG`.
+	/// Any invalid directives are ignored, with a warning once built.
+	#[must_use]
+	pub fn filter(self, directives: &str) -> Self {
+		Self {
+			filter: directives.to_owned(),
+			..self
+		}
+	}
+
+	/// Sets the format
+	#[must_use]
+	pub fn format(self, format: FileFormat) -> Self {
+		Self { format, ..self }
+	}
+
+	/// Sets the file
+	#[must_use]
+	pub fn path(self, path: &Path) -> Self {
+		Self {
+			path: Some(path.to_owned()),
+			..self
+		}
+	}
+
+	/// Sets the rotation
+	#[must_use]
+	pub fn rotation(self, rotation: Rotation) -> Self {
+		Self {
+			rotation: Some(rotation),
+			..self
+		}
+	}
+
+	/// Makes writing to this sink non-blocking.
+	///
+	/// See [`LoggerBuilder::file_non_blocking`](crate::LoggerBuilder::file_non_blocking).
+	#[must_use]
+	pub fn non_blocking(self, capacity: usize, policy: OverflowPolicy) -> Self {
+		Self {
+			non_blocking: Some((capacity, policy)),
+			..self
+		}
+	}
+}
+
+impl Default for FileSink {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Handle to a file sink of a [`Logger`](crate::Logger)
+#[derive(Debug)]
+pub struct Sink {
+	/// Name
+	name: String,
+
+	/// File writer
+	writer: FileWriter,
+
+	/// Filter
+	filter: Arc<ReloadFilter>,
+}
+
+impl Sink {
+	/// Creates a new sink
+	pub(crate) const fn new(name: String, writer: FileWriter, filter: Arc<ReloadFilter>) -> Self {
+		Self { name, writer, filter }
+	}
+
+	/// Returns the file writer of this sink
+	pub(crate) const fn writer(&self) -> &FileWriter {
+		&self.writer
+	}
+
+	/// Returns the filter of this sink
+	#[cfg(feature = "config")]
+	pub(crate) const fn reload_filter(&self) -> &Arc<ReloadFilter> {
+		&self.filter
+	}
+
+	/// Returns the name of this sink
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Sets or replaces the file of this sink.
+	///
+	/// See [`Logger::set_file`](crate::Logger::set_file).
+	pub fn set_file(&self, path: Option<&Path>) {
+		match path {
+			Some(path) => match self.writer.set_file(path) {
+				Ok(()) => tracing::info!("Logging sink {:?} to file: {path:?}", self.name),
+				Err(err) => {
+					tracing::warn!("Unable to create log file {path:?} for sink {:?}: {err}", self.name);
+					self.writer.set_empty();
+				},
+			},
+			None => self.writer.set_empty(),
+		}
+	}
+
+	/// Flushes this sink
+	pub fn flush(&self) {
+		if let Err(err) = self.writer.flush() {
+			tracing::warn!("Unable to flush log sink {:?}: {err}", self.name);
+		}
+	}
+
+	/// Returns the current filter directives
+	#[must_use]
+	pub fn filter(&self) -> String {
+		self.filter.directives()
+	}
+
+	/// Replaces the filter directives
+	pub fn set_filter(&self, directives: &str) -> Result<(), FilterError> {
+		self.filter.set(directives)
+	}
+
+	/// Adds filter directives.
+	///
+	/// Overrides any existing directives for the same targets.
+	pub fn add_filter(&self, directives: &str) -> Result<(), FilterError> {
+		self.filter.add(directives)
+	}
+}
@@ -2,12 +2,16 @@
 
 // Imports
 use {
+	crate::filter::ReloadFilter,
 	tracing::{Subscriber, metadata::LevelFilter},
 	tracing_subscriber::{EnvFilter, Layer, fmt::MakeWriter, registry::LookupSpan},
 };
 
 /// Creates the terminal layer
-pub fn layer<W, S>(stderr: W, default_filters: impl IntoIterator<Item = (Option<&'_ str>, &'_ str)>) -> impl Layer<S>
+pub fn layer<W, S>(
+	stderr: W,
+	default_filters: impl IntoIterator<Item = (Option<&'_ str>, &'_ str)>,
+) -> (impl Layer<S>, ReloadFilter)
 where
 	W: for<'a> MakeWriter<'a> + 'static,
 	S: Subscriber + for<'a> LookupSpan<'a>,
@@ -18,9 +22,10 @@ where
 	#[cfg(debug_assertions)]
 	let layer = layer.with_file(true).with_line_number(true).with_thread_names(true);
 
-	layer.with_filter(
-		EnvFilter::builder()
-			.with_default_directive(LevelFilter::INFO.into())
-			.parse_lossy(env),
-	)
+	let (filter, filter_layer) = ReloadFilter::new(
+		EnvFilter::builder().with_default_directive(LevelFilter::INFO.into()),
+		&env,
+	);
+
+	(layer.with_filter(filter_layer), filter)
 }
@@ -0,0 +1,39 @@
+//! Common test utilities
+
+// Imports
+use {
+	std::{
+		io,
+		sync::{Arc, Mutex},
+	},
+	tracing_subscriber::fmt::MakeWriter,
+};
+
+/// Shared output
+#[derive(Clone, Default)]
+pub struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+	/// Returns everything written so far
+	pub fn contents(&self) -> String {
+		String::from_utf8(self.0.lock().expect("Poisoned").clone()).expect("Output wasn't utf-8")
+	}
+}
+
+impl io::Write for Output {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.lock().expect("Poisoned").write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl MakeWriter<'_> for Output {
+	type Writer = Self;
+
+	fn make_writer(&self) -> Self::Writer {
+		self.clone()
+	}
+}
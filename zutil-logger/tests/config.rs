@@ -0,0 +1,81 @@
+//! Config tests
+
+// Imports
+use {
+	std::path::Path,
+	zutil_logger::{ConfigError, FileFormat, LoggerConfig, OverflowPolicy, RotationPeriod},
+};
+
+
+#[test]
+fn from_toml() {
+	let config = LoggerConfig::from_toml(
+		r#"
+		[stderr]
+		filter = "info"
+		filters = { "app::net" = "debug" }
+
+		[file]
+		path = "app.log"
+		format = "logfmt"
+		non_blocking = { capacity = 128, policy = "drop" }
+		rotation = { max_size = 1000, period = "daily", keep = 3, compress = true }
+
+		[sinks.audit]
+		filter = "audit=info"
+		path = "audit.log"
+		non_blocking = { capacity = 16 }
+		"#,
+	)
+	.expect("Unable to parse configuration");
+
+	assert_eq!(config.stderr.filter.as_deref(), Some("info"));
+	assert_eq!(config.stderr.filters["app::net"], "debug");
+
+	assert_eq!(config.file.filter, None);
+	assert_eq!(config.file.path.as_deref(), Some(Path::new("app.log")));
+	assert_eq!(config.file.format, Some(FileFormat::Logfmt));
+	let non_blocking = config.file.non_blocking.expect("Missing non-blocking configuration");
+	assert_eq!(non_blocking.capacity, 128);
+	assert_eq!(non_blocking.policy, OverflowPolicy::Drop);
+	let rotation = config.file.rotation.expect("Missing rotation configuration");
+	assert_eq!(rotation.max_size, Some(1000));
+	assert_eq!(rotation.period, Some(RotationPeriod::Daily));
+	assert_eq!(rotation.keep, Some(3));
+	assert!(rotation.compress);
+
+	let audit = &config.sinks["audit"];
+	assert_eq!(audit.filter.as_deref(), Some("audit=info"));
+	assert_eq!(audit.path.as_deref(), Some(Path::new("audit.log")));
+	assert_eq!(audit.format, None);
+	let audit_non_blocking = audit.non_blocking.as_ref().expect("Missing non-blocking configuration");
+	assert_eq!(audit_non_blocking.capacity, 16);
+	assert_eq!(audit_non_blocking.policy, OverflowPolicy::default());
+}
+
+#[test]
+fn empty() {
+	let config = LoggerConfig::from_toml("").expect("Unable to parse configuration");
+	assert_eq!(config.stderr.filter, None);
+	assert!(config.file.path.is_none());
+	assert!(config.sinks.is_empty());
+}
+
+#[test]
+fn invalid() {
+	for s in [
+		"[stderr]\nfliter = \"info\"",
+		"[file]\nformat = \"xml\"",
+		"[file]\nnon_blocking = { policy = \"drop\" }",
+	] {
+		assert!(matches!(LoggerConfig::from_toml(s), Err(ConfigError::Parse(_))), "{s}");
+	}
+}
+
+#[test]
+fn from_file_missing() {
+	assert!(matches!(
+		LoggerConfig::from_file(Path::new("/nonexistent/zutil-logger.toml")),
+		Err(ConfigError::Read(_))
+	));
+}
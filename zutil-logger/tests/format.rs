@@ -0,0 +1,54 @@
+//! Format tests
+
+// Modules
+mod common;
+
+// Imports
+use {self::common::Output, zutil_logger::Logfmt};
+
+/// Formats all events emitted by `f` with logfmt, and returns
+/// them, without the timestamps
+fn logfmt(f: impl FnOnce()) -> Vec<String> {
+	let output = Output::default();
+	let subscriber = tracing_subscriber::fmt()
+		.with_writer(output.clone())
+		.event_format(Logfmt)
+		.finish();
+	tracing::subscriber::with_default(subscriber, f);
+
+	output
+		.contents()
+		.lines()
+		.map(|line| {
+			let (ts, rest) = line.split_once(' ').expect("Missing timestamp");
+			assert!(ts.starts_with("ts="), "{line}");
+			rest.to_owned()
+		})
+		.collect()
+}
+
+#[test]
+fn logfmt_fields() {
+	let lines = logfmt(|| tracing::info!(target: "app", count = 5, name = "abc", "Hello"));
+	assert_eq!(lines, ["level=info target=app msg=Hello count=5 name=abc"]);
+}
+
+#[test]
+fn logfmt_quoting() {
+	let lines = logfmt(|| {
+		tracing::warn!(target: "app", empty = "", path = r"C:\logs", "Hello, \"world\"\nbye=1");
+	});
+	assert_eq!(lines, [
+		r#"level=warn target=app msg="Hello, \"world\"\nbye=1" empty="" path="C:\\logs""#
+	]);
+}
+
+#[test]
+fn logfmt_spans() {
+	let lines = logfmt(|| {
+		let _outer = tracing::info_span!("outer").entered();
+		let _inner = tracing::info_span!("inner").entered();
+		tracing::info!(target: "app", "Nested");
+	});
+	assert_eq!(lines, ["level=info target=app spans=outer>inner msg=Nested"]);
+}
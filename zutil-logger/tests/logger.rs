@@ -0,0 +1,22 @@
+//! Logger tests
+
+// Modules
+mod common;
+
+// Imports
+use {self::common::Output, zutil_logger::Logger};
+
+// Note: Only a single test may be in this file, since the logger is global.
+#[test]
+fn build() {
+	let output = Output::default();
+	let _logger = Logger::builder().stderr(output.clone()).build();
+
+	tracing::info!("Hello");
+	tracing::debug!("Hidden");
+
+	let output = output.contents();
+	assert!(output.contains("Successfully initialized logger"), "{output}");
+	assert!(output.contains("Hello"), "{output}");
+	assert!(!output.contains("Hidden"), "{output}");
+}
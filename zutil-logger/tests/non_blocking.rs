@@ -0,0 +1,31 @@
+//! Non-blocking writer tests
+
+// Imports
+use {
+	std::{env, fs, process},
+	zutil_logger::{Logger, OverflowPolicy},
+};
+
+
+// Note: Only a single test may be in this file, since the logger is global.
+#[test]
+fn flush_on_drop() {
+	let dir = env::temp_dir().join(format!("zutil-logger-{}-flush_on_drop", process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).expect("Unable to create temporary directory");
+	let log_path = dir.join("app.log");
+
+	let logger = Logger::builder()
+		.file_path(&log_path)
+		.file_non_blocking(4, OverflowPolicy::Block)
+		.build();
+	for idx in 0..100 {
+		tracing::info!("Event #{idx}");
+	}
+	drop(logger);
+
+	let log = fs::read_to_string(&log_path).expect("Unable to read log file");
+	for idx in 0..100 {
+		assert!(log.contains(&format!("Event #{idx}\n")), "{log}");
+	}
+}
@@ -0,0 +1,56 @@
+//! Panic hook tests
+
+// Imports
+use {
+	std::{
+		env,
+		fs,
+		panic,
+		process,
+		sync::{
+			Arc,
+			atomic::{self, AtomicBool},
+		},
+		thread,
+	},
+	zutil_logger::{Logger, OverflowPolicy, PanicHook},
+};
+
+
+#[test]
+fn panic_hook() {
+	let dir = env::temp_dir().join(format!("zutil-logger-{}-panic_hook", process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).expect("Unable to create temporary directory");
+	let log_path = dir.join("app.log");
+	let crash_report_path = dir.join("crash.txt");
+
+	let prev_hook_called = Arc::new(AtomicBool::new(false));
+	panic::set_hook(Box::new({
+		let prev_hook_called = Arc::clone(&prev_hook_called);
+		move |_| prev_hook_called.store(true, atomic::Ordering::Relaxed)
+	}));
+
+	let _logger = Logger::builder()
+		.file_path(&log_path)
+		.file_non_blocking(16, OverflowPolicy::Block)
+		.panic_hook(PanicHook::new().crash_report(&crash_report_path))
+		.build();
+
+	thread::Builder::new()
+		.name("panicker".to_owned())
+		.spawn(|| panic!("Oh no"))
+		.expect("Unable to spawn thread")
+		.join()
+		.expect_err("Thread should have panicked");
+
+	assert!(prev_hook_called.load(atomic::Ordering::Relaxed));
+
+	let crash_report = fs::read_to_string(&crash_report_path).expect("Unable to read crash report");
+	assert!(crash_report.contains("Thread: panicker"), "{crash_report}");
+	assert!(crash_report.contains("Message: Oh no"), "{crash_report}");
+
+	// Note: The hook flushes the log file, so the panic must already be there
+	let log = fs::read_to_string(&log_path).expect("Unable to read log file");
+	assert!(log.contains(r#"Thread "panicker" panicked at"#), "{log}");
+}
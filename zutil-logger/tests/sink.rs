@@ -0,0 +1,53 @@
+//! Sink tests
+
+// Imports
+use {
+	serde_json::Value,
+	std::{env, fs, process},
+	zutil_logger::{FileFormat, FileSink, Logger, OverflowPolicy},
+};
+
+
+// Note: Only a single test may be in this file, since the logger is global.
+#[test]
+fn routing() {
+	let dir = env::temp_dir().join(format!("zutil-logger-{}-routing", process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).expect("Unable to create temporary directory");
+	let log_path = dir.join("app.log");
+	let audit_path = dir.join("audit.log");
+
+	let logger = Logger::builder()
+		.file_path(&log_path)
+		.file_sink(
+			"audit",
+			FileSink::new()
+				.filter("audit=info")
+				.format(FileFormat::Json)
+				.path(&audit_path)
+				.non_blocking(4, OverflowPolicy::Block),
+		)
+		.build();
+	tracing::info!(target: "audit", user = "admin", "Logged in");
+	tracing::debug!(target: "audit", "Hidden from audit");
+	tracing::info!(target: "app", "Started");
+	drop(logger);
+
+	let audit = fs::read_to_string(&audit_path).expect("Unable to read audit log");
+	let audit = audit
+		.lines()
+		.map(|line| serde_json::from_str::<Value>(line).expect("Line wasn't valid json"))
+		.collect::<Vec<_>>();
+	let [logged_in] = &*audit else {
+		panic!("Expected a single audit event, found {audit:?}");
+	};
+	assert_eq!(logged_in["target"], "audit");
+	assert_eq!(logged_in["fields"]["message"], "Logged in");
+	assert_eq!(logged_in["fields"]["user"], "admin");
+
+	let log = fs::read_to_string(&log_path).expect("Unable to read log file");
+	assert!(log.contains("Logged in"), "{log}");
+	assert!(log.contains("Hidden from audit"), "{log}");
+	assert!(log.contains("Started"), "{log}");
+	assert!(!log.starts_with('{'), "{log}");
+}